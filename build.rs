@@ -1,24 +1,60 @@
-use std::{env::var, fs::read_dir, process::Command};
+use retro_wicket_aseprite::Aseprite;
+use std::{
+    env::var,
+    fs::{read, read_dir, read_to_string, File},
+    io::BufWriter,
+    process::Command,
+};
 
 fn main() {
     println!("cargo::rerun-if-changed=src/sprites");
+    println!("cargo::rerun-if-changed=src/aseprite_path");
+    let aseprite_path = read_to_string("src/aseprite_path").ok();
     let paths = read_dir("./src/sprites")
         .unwrap()
         .map(|entry| entry.unwrap().path().to_str().unwrap().to_string());
     for path in paths {
-        assert!(Command::new(include_str!("src/aseprite_path").trim())
-            .arg("-b")
-            .arg(&path)
-            .arg("--save-as")
-            .arg(format!(
-                "{}/{}.png",
-                var("OUT_DIR").unwrap(),
-                path.split(['/', '.']).rev().nth(1).unwrap()
-            ))
-            .spawn()
-            .unwrap()
-            .wait()
+        let name = path.split(['/', '.']).rev().nth(1).unwrap();
+        if let Some(aseprite_path) = &aseprite_path {
+            assert!(Command::new(aseprite_path.trim())
+                .arg("-b")
+                .arg(&path)
+                .arg("--save-as")
+                .arg(format!("{}/{name}.png", var("OUT_DIR").unwrap()))
+                .spawn()
+                .unwrap()
+                .wait()
+                .unwrap()
+                .success());
+        } else {
+            export(&path, name);
+        }
+    }
+}
+
+/// Exports the sprite the same way `aseprite --save-as` does, numbering the files from 1 when
+/// there is more than one frame.
+fn export(path: &str, name: &str) {
+    let aseprite = Aseprite::parse(&read(path).unwrap())
+        .unwrap_or_else(|error| panic!("failed to decode {path}: {error}"));
+    for frame in 0..aseprite.frames.len() {
+        let suffix = if aseprite.frames.len() == 1 {
+            String::new()
+        } else {
+            (frame + 1).to_string()
+        };
+        let file = File::create(format!("{}/{name}{suffix}.png", var("OUT_DIR").unwrap())).unwrap();
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            aseprite.width as u32,
+            aseprite.height as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
             .unwrap()
-            .success());
+            .write_image_data(&aseprite.render(frame).unwrap())
+            .unwrap();
     }
 }
@@ -0,0 +1,648 @@
+//! Decoder for `.aseprite` files, so sprites can be exported without the Aseprite binary.
+//!
+//! Format reference: <https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md>
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_lossless,
+    clippy::cast_sign_loss
+)]
+use flate2::read::ZlibDecoder;
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    io::{self, Read},
+};
+
+#[derive(Debug)]
+pub enum Error {
+    UnexpectedEof,
+    BadMagic { expected: u16, found: u16 },
+    UnsupportedColourDepth(u16),
+    Decompress(io::Error),
+    MissingFrame(usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::BadMagic { expected, found } => {
+                write!(
+                    f,
+                    "expected magic number {expected:#06x}, found {found:#06x}"
+                )
+            }
+            Self::UnsupportedColourDepth(depth) => {
+                write!(f, "unsupported colour depth of {depth} bits per pixel")
+            }
+            Self::Decompress(error) => write!(f, "failed to decompress cel: {error}"),
+            Self::MissingFrame(index) => write!(f, "frame {index} does not exist"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Decompress(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColourDepth {
+    Rgba,
+    Grayscale,
+    Indexed,
+}
+
+impl ColourDepth {
+    const fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba => 4,
+            Self::Grayscale => 2,
+            Self::Indexed => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKind {
+    Normal,
+    Group,
+    Tilemap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    pub name: String,
+    pub kind: LayerKind,
+    pub visible: bool,
+    pub reference: bool,
+    pub child_level: u16,
+    pub opacity: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: Direction,
+    /// Number of times to play the tag, where 0 means forever.
+    pub repeat: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Duration in milliseconds.
+    pub duration: u16,
+    cels: Vec<Cel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cel {
+    layer: usize,
+    x: i16,
+    y: i16,
+    opacity: u8,
+    z_index: i16,
+    content: CelContent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CelContent {
+    Image {
+        width: u16,
+        height: u16,
+        pixels: Vec<u8>,
+    },
+    Linked(usize),
+    Tilemap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aseprite {
+    pub width: u16,
+    pub height: u16,
+    pub colour_depth: ColourDepth,
+    pub layers: Vec<Layer>,
+    pub frames: Vec<Frame>,
+    pub tags: Vec<Tag>,
+    pub palette: Vec<[u8; 4]>,
+    transparent_index: u8,
+    layer_opacity_valid: bool,
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::UnexpectedEof);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn short(&mut self) -> Result<i16, Error> {
+        Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn dword(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let len = self.word()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn magic(&mut self, expected: u16) -> Result<(), Error> {
+        let found = self.word()?;
+        if found == expected {
+            Ok(())
+        } else {
+            Err(Error::BadMagic { expected, found })
+        }
+    }
+}
+
+impl Aseprite {
+    const FILE_MAGIC: u16 = 0xa5e0;
+    const FRAME_MAGIC: u16 = 0xf1fa;
+
+    /// # Errors
+    ///
+    /// Returns an error if the file is truncated, corrupt or uses an unknown colour depth.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader { bytes };
+        let _file_size = reader.dword()?;
+        reader.magic(Self::FILE_MAGIC)?;
+        let frame_count = reader.word()?;
+        let width = reader.word()?;
+        let height = reader.word()?;
+        let colour_depth = match reader.word()? {
+            32 => ColourDepth::Rgba,
+            16 => ColourDepth::Grayscale,
+            8 => ColourDepth::Indexed,
+            depth => return Err(Error::UnsupportedColourDepth(depth)),
+        };
+        let flags = reader.dword()?;
+        reader.take(10)?;
+        let transparent_index = reader.byte()?;
+        reader.take(128 - 29)?;
+
+        let mut aseprite = Self {
+            width,
+            height,
+            colour_depth,
+            layers: Vec::new(),
+            frames: Vec::new(),
+            tags: Vec::new(),
+            palette: Vec::new(),
+            transparent_index,
+            layer_opacity_valid: flags & 1 != 0,
+        };
+        let layers_have_uuid = flags & 4 != 0;
+        let mut has_new_palette = false;
+        for _ in 0..frame_count {
+            let frame_size = reader.dword()? as usize;
+            let mut frame_reader = Reader {
+                bytes: reader.take(frame_size.saturating_sub(4))?,
+            };
+            frame_reader.magic(Self::FRAME_MAGIC)?;
+            let old_chunk_count = frame_reader.word()?;
+            let duration = frame_reader.word()?;
+            frame_reader.take(2)?;
+            let chunk_count = match frame_reader.dword()? {
+                0 => old_chunk_count as u32,
+                count => count,
+            };
+            let mut frame = Frame {
+                duration,
+                cels: Vec::new(),
+            };
+            for _ in 0..chunk_count {
+                let chunk_size = frame_reader.dword()? as usize;
+                let chunk_type = frame_reader.word()?;
+                let mut chunk = Reader {
+                    bytes: frame_reader.take(chunk_size.saturating_sub(6))?,
+                };
+                match chunk_type {
+                    0x0004 | 0x0011 if !has_new_palette => {
+                        aseprite.read_old_palette(&mut chunk, chunk_type == 0x0011)?;
+                    }
+                    0x2004 => aseprite
+                        .layers
+                        .push(Self::read_layer(&mut chunk, layers_have_uuid)?),
+                    0x2005 => frame.cels.push(aseprite.read_cel(&mut chunk)?),
+                    0x2018 => aseprite.tags = Self::read_tags(&mut chunk)?,
+                    0x2019 => {
+                        has_new_palette = true;
+                        aseprite.read_palette(&mut chunk)?;
+                    }
+                    _ => {}
+                }
+            }
+            aseprite.frames.push(frame);
+        }
+        Ok(aseprite)
+    }
+
+    fn read_layer(chunk: &mut Reader, has_uuid: bool) -> Result<Layer, Error> {
+        let flags = chunk.word()?;
+        let kind = match chunk.word()? {
+            1 => LayerKind::Group,
+            2 => LayerKind::Tilemap,
+            _ => LayerKind::Normal,
+        };
+        let child_level = chunk.word()?;
+        chunk.take(6)?;
+        let opacity = chunk.byte()?;
+        chunk.take(3)?;
+        let name = chunk.string()?;
+        if kind == LayerKind::Tilemap {
+            chunk.dword()?;
+        }
+        if has_uuid {
+            chunk.take(16)?;
+        }
+        Ok(Layer {
+            name,
+            kind,
+            visible: flags & 1 != 0,
+            reference: flags & 64 != 0,
+            child_level,
+            opacity,
+        })
+    }
+
+    fn read_cel(&self, chunk: &mut Reader) -> Result<Cel, Error> {
+        let layer = chunk.word()? as usize;
+        let x = chunk.short()?;
+        let y = chunk.short()?;
+        let opacity = chunk.byte()?;
+        let cel_type = chunk.word()?;
+        let z_index = chunk.short()?;
+        chunk.take(5)?;
+        let content = match cel_type {
+            0 | 2 => {
+                let width = chunk.word()?;
+                let height = chunk.word()?;
+                let len = width as usize * height as usize * self.colour_depth.bytes_per_pixel();
+                let pixels = if cel_type == 0 {
+                    chunk.take(len)?.to_vec()
+                } else {
+                    let mut pixels = Vec::with_capacity(len);
+                    ZlibDecoder::new(chunk.bytes)
+                        .read_to_end(&mut pixels)
+                        .map_err(Error::Decompress)?;
+                    pixels
+                };
+                if pixels.len() < len {
+                    return Err(Error::UnexpectedEof);
+                }
+                CelContent::Image {
+                    width,
+                    height,
+                    pixels,
+                }
+            }
+            1 => CelContent::Linked(chunk.word()? as usize),
+            _ => CelContent::Tilemap,
+        };
+        Ok(Cel {
+            layer,
+            x,
+            y,
+            opacity,
+            z_index,
+            content,
+        })
+    }
+
+    fn read_tags(chunk: &mut Reader) -> Result<Vec<Tag>, Error> {
+        let count = chunk.word()?;
+        chunk.take(8)?;
+        (0..count)
+            .map(|_| {
+                let from = chunk.word()? as usize;
+                let to = chunk.word()? as usize;
+                let direction = match chunk.byte()? {
+                    1 => Direction::Reverse,
+                    2 => Direction::PingPong,
+                    3 => Direction::PingPongReverse,
+                    _ => Direction::Forward,
+                };
+                let repeat = chunk.word()?;
+                chunk.take(10)?;
+                let name = chunk.string()?;
+                Ok(Tag {
+                    name,
+                    from,
+                    to,
+                    direction,
+                    repeat,
+                })
+            })
+            .collect()
+    }
+
+    fn read_palette(&mut self, chunk: &mut Reader) -> Result<(), Error> {
+        let size = chunk.dword()? as usize;
+        let first = chunk.dword()? as usize;
+        let last = chunk.dword()? as usize;
+        chunk.take(8)?;
+        self.palette.resize(size.max(self.palette.len()), [0; 4]);
+        for index in first..=last {
+            let flags = chunk.word()?;
+            let colour = chunk.take(4)?.try_into().unwrap();
+            if flags & 1 != 0 {
+                chunk.string()?;
+            }
+            if let Some(entry) = self.palette.get_mut(index) {
+                *entry = colour;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_old_palette(&mut self, chunk: &mut Reader, six_bit: bool) -> Result<(), Error> {
+        let packets = chunk.word()?;
+        let mut index = 0;
+        for _ in 0..packets {
+            index += chunk.byte()? as usize;
+            let count = match chunk.byte()? {
+                0 => 256,
+                count => count as usize,
+            };
+            for _ in 0..count {
+                let [red, green, blue] =
+                    [chunk.byte()?, chunk.byte()?, chunk.byte()?].map(|channel| {
+                        if six_bit {
+                            channel << 2 | channel >> 4
+                        } else {
+                            channel
+                        }
+                    });
+                if self.palette.len() <= index {
+                    self.palette.resize(index + 1, [0; 4]);
+                }
+                self.palette[index] = [red, green, blue, 255];
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flattens the visible layers of a frame into RGBA8 pixels, like `aseprite --save-as` does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame, or a frame linked to by one of its cels, does not exist.
+    pub fn render(&self, frame: usize) -> Result<Vec<u8>, Error> {
+        let mut canvas = vec![0; self.width as usize * self.height as usize * 4];
+        let visible = self.effective_visibility();
+        let mut cels = self
+            .frames
+            .get(frame)
+            .ok_or(Error::MissingFrame(frame))?
+            .cels
+            .iter()
+            .filter(|cel| visible.get(cel.layer).copied().unwrap_or(false))
+            .collect::<Vec<_>>();
+        cels.sort_by_key(|cel| (cel.layer as i32 + cel.z_index as i32, cel.z_index));
+        for cel in cels {
+            let content = match cel.content {
+                CelContent::Linked(linked) => {
+                    &self
+                        .frames
+                        .get(linked)
+                        .ok_or(Error::MissingFrame(linked))?
+                        .cels
+                        .iter()
+                        .find(|linked| linked.layer == cel.layer)
+                        .ok_or(Error::MissingFrame(linked))?
+                        .content
+                }
+                ref content => content,
+            };
+            let CelContent::Image {
+                width,
+                height,
+                ref pixels,
+            } = *content
+            else {
+                continue;
+            };
+            let layer_opacity = if self.layer_opacity_valid {
+                self.layers[cel.layer].opacity
+            } else {
+                255
+            };
+            let opacity = cel.opacity as f32 / 255. * layer_opacity as f32 / 255.;
+            self.draw_cel(&mut canvas, cel, width, height, pixels, opacity);
+        }
+        Ok(canvas)
+    }
+
+    fn draw_cel(
+        &self,
+        canvas: &mut [u8],
+        cel: &Cel,
+        width: u16,
+        height: u16,
+        pixels: &[u8],
+        opacity: f32,
+    ) {
+        let bytes_per_pixel = self.colour_depth.bytes_per_pixel();
+        for cel_y in 0..height as i32 {
+            let y = cel.y as i32 + cel_y;
+            if !(0..self.height as i32).contains(&y) {
+                continue;
+            }
+            for cel_x in 0..width as i32 {
+                let x = cel.x as i32 + cel_x;
+                if !(0..self.width as i32).contains(&x) {
+                    continue;
+                }
+                let source = (cel_y as usize * width as usize + cel_x as usize) * bytes_per_pixel;
+                let [red, green, blue, alpha] =
+                    self.pixel_to_rgba(&pixels[source..source + bytes_per_pixel]);
+                let destination = (y as usize * self.width as usize + x as usize) * 4;
+                blend(
+                    &mut canvas[destination..destination + 4],
+                    [red, green, blue],
+                    alpha as f32 / 255. * opacity,
+                );
+            }
+        }
+    }
+
+    fn pixel_to_rgba(&self, pixel: &[u8]) -> [u8; 4] {
+        match self.colour_depth {
+            ColourDepth::Rgba => pixel.try_into().unwrap(),
+            ColourDepth::Grayscale => [pixel[0], pixel[0], pixel[0], pixel[1]],
+            ColourDepth::Indexed if pixel[0] == self.transparent_index => [0; 4],
+            ColourDepth::Indexed => self
+                .palette
+                .get(pixel[0] as usize)
+                .copied()
+                .unwrap_or([0; 4]),
+        }
+    }
+
+    /// Whether each layer is drawn, taking hidden parent groups into account.
+    fn effective_visibility(&self) -> Vec<bool> {
+        let mut parents = Vec::<bool>::new();
+        self.layers
+            .iter()
+            .map(|layer| {
+                let level = layer.child_level as usize;
+                let parent_visible = level == 0 || parents.get(level - 1).copied().unwrap_or(true);
+                let visible = layer.visible && parent_visible;
+                parents.truncate(level);
+                parents.push(visible);
+                visible && !layer.reference && layer.kind != LayerKind::Group
+            })
+            .collect()
+    }
+}
+
+fn blend(destination: &mut [u8], colour: [u8; 3], alpha: f32) {
+    if alpha <= 0. {
+        return;
+    }
+    let destination_alpha = destination[3] as f32 / 255.;
+    let out_alpha = alpha + destination_alpha * (1. - alpha);
+    for (channel, source) in destination.iter_mut().zip(colour) {
+        *channel = ((source as f32 * alpha + *channel as f32 * destination_alpha * (1. - alpha))
+            / out_alpha)
+            .round() as u8;
+    }
+    destination[3] = (out_alpha * 255.).round() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RGBA: &[u8] = include_bytes!("../tests/fixtures/rgba.aseprite");
+    const INDEXED: &[u8] = include_bytes!("../tests/fixtures/indexed.aseprite");
+    const TAGGED: &[u8] = include_bytes!("../tests/fixtures/tagged.aseprite");
+
+    #[test]
+    fn renders_one_rgba_frame() {
+        let aseprite = Aseprite::parse(RGBA).unwrap();
+        assert_eq!((aseprite.width, aseprite.height), (2, 2));
+        assert_eq!(aseprite.colour_depth, ColourDepth::Rgba);
+        assert_eq!(aseprite.frames.len(), 1);
+        assert_eq!(aseprite.frames[0].duration, 100);
+        assert_eq!(aseprite.layers[0].name, "Layer 1");
+        assert_eq!(
+            aseprite.render(0).unwrap(),
+            [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [0; 4]].concat()
+        );
+    }
+
+    #[test]
+    fn looks_indexed_pixels_up_in_the_palette() {
+        let aseprite = Aseprite::parse(INDEXED).unwrap();
+        assert_eq!(aseprite.colour_depth, ColourDepth::Indexed);
+        assert_eq!(
+            aseprite.palette,
+            [[0, 0, 0, 0], [255, 255, 255, 255], [0, 0, 0, 255]]
+        );
+        // The cel starts one pixel in, and index 0 is transparent.
+        let white = [255; 4];
+        let black = [0, 0, 0, 255];
+        assert_eq!(
+            aseprite.render(0).unwrap(),
+            [[0; 4], white, black, [0; 4], [0; 4], white].concat()
+        );
+    }
+
+    #[test]
+    fn reads_tags_durations_and_linked_cels() {
+        let aseprite = Aseprite::parse(TAGGED).unwrap();
+        assert_eq!(
+            aseprite
+                .frames
+                .iter()
+                .map(|frame| frame.duration)
+                .collect::<Vec<_>>(),
+            [100, 200, 300]
+        );
+        assert_eq!(
+            aseprite.tags,
+            [
+                Tag {
+                    name: "idle".to_string(),
+                    from: 0,
+                    to: 0,
+                    direction: Direction::Forward,
+                    repeat: 0,
+                },
+                Tag {
+                    name: "swing".to_string(),
+                    from: 1,
+                    to: 2,
+                    direction: Direction::PingPong,
+                    repeat: 1,
+                },
+            ]
+        );
+        // Drawn at half opacity.
+        assert_eq!(
+            aseprite.render(1).unwrap(),
+            [[0, 255, 0, 128], [0, 0, 255, 128]].concat()
+        );
+        assert_eq!(aseprite.render(2).unwrap(), aseprite.render(0).unwrap());
+        assert!(matches!(aseprite.render(3), Err(Error::MissingFrame(3))));
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        assert!(matches!(
+            Aseprite::parse(&RGBA[..100]),
+            Err(Error::UnexpectedEof)
+        ));
+        let mut bytes = RGBA.to_vec();
+        bytes[4] = 0;
+        assert!(matches!(
+            Aseprite::parse(&bytes),
+            Err(Error::BadMagic {
+                expected: 0xa5e0,
+                ..
+            })
+        ));
+        let mut bytes = RGBA.to_vec();
+        bytes[12] = 24;
+        assert!(matches!(
+            Aseprite::parse(&bytes),
+            Err(Error::UnsupportedColourDepth(24))
+        ));
+    }
+}
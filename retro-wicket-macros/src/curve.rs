@@ -0,0 +1,525 @@
+use itertools::Itertools;
+use nom::{
+    bytes::complete::{tag, take_while1},
+    character::complete::char,
+    combinator::{all_consuming, map_res, opt, recognize},
+    number::complete::recognize_float,
+    sequence::preceded,
+    IResult, Parser,
+};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::quote;
+use std::str::FromStr;
+use syn::{
+    parse::{Parse, ParseStream},
+    Error, Ident, Lit, Token,
+};
+
+/// A curve described in `poly!` or `poly_consts!`. Coefficients are stored highest power first,
+/// like `Polynomial`.
+pub enum Curve {
+    Polynomial(Vec<f64>),
+    /// Each piece applies below its bound, with the last one unbounded.
+    Piecewise(Vec<(f64, Vec<f64>)>),
+}
+
+impl Parse for Curve {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(syn::token::Brace) {
+            let keyword: Ident = input.parse()?;
+            if keyword == "piecewise" {
+                let content;
+                syn::braced!(content in input);
+                return parse_piecewise(&content);
+            }
+            return Err(Error::new_spanned(keyword, "expected `piecewise`"));
+        }
+        if input.peek(Ident) && input.peek2(syn::token::Bracket) {
+            let keyword: Ident = input.parse()?;
+            if keyword == "spline" {
+                let content;
+                syn::bracketed!(content in input);
+                return parse_spline(&content);
+            }
+            return Err(Error::new_spanned(keyword, "expected `spline`"));
+        }
+        let tokens = tokens_until(input, |input| input.peek(Token![;]))?;
+        expression(tokens).map(Curve::Polynomial)
+    }
+}
+
+/// Takes tokens until `end` matches or the input runs out.
+fn tokens_until(
+    input: ParseStream,
+    end: impl Fn(ParseStream) -> bool,
+) -> syn::Result<Vec<TokenTree>> {
+    let mut tokens = Vec::new();
+    while !input.is_empty() && !end(input) {
+        tokens.push(input.parse()?);
+    }
+    Ok(tokens)
+}
+
+fn is_punct(token: &TokenTree, char: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == char)
+}
+
+/// Parses `y = polynomial`, where `y =` is optional.
+fn expression(mut tokens: Vec<TokenTree>) -> syn::Result<Vec<f64>> {
+    if matches!(&tokens[..], [TokenTree::Ident(y), equals, ..] if y == "y" && is_punct(equals, '='))
+    {
+        tokens.drain(..2);
+    }
+    polynomial(&tokens)
+}
+
+/// Parses a sum of terms like `-2.5e-3x^2`, `+ x` or `4`, adding up terms with the same power.
+fn polynomial(tokens: &[TokenTree]) -> syn::Result<Vec<f64>> {
+    if tokens.is_empty() {
+        return Err(Error::new(
+            proc_macro2::Span::call_site(),
+            "expected a polynomial",
+        ));
+    }
+    let mut coefficients = Vec::<f64>::new();
+    let mut rest = tokens;
+    while !rest.is_empty() {
+        let negative = is_punct(&rest[0], '-');
+        if negative || is_punct(&rest[0], '+') {
+            rest = &rest[1..];
+        }
+        let len = rest
+            .iter()
+            .position(|token| is_punct(token, '+') || is_punct(token, '-'))
+            .unwrap_or(rest.len());
+        let (term, after) = rest.split_at(len);
+        let stream = term.iter().cloned().collect::<TokenStream2>();
+        let text = stream.to_string().replace(' ', "");
+        if term.is_empty() {
+            let sign = &tokens[tokens.len() - rest.len() - 1..];
+            return Err(Error::new_spanned(
+                sign.iter().take(2).cloned().collect::<TokenStream2>(),
+                "expected a term after this sign",
+            ));
+        }
+        let Ok((_, (coefficient, power))) = all_consuming(parse_term)(&text) else {
+            return Err(Error::new_spanned(
+                stream,
+                format!(
+                    "invalid term `{text}`: expected a number, `x`, or `x^n` with a coefficient"
+                ),
+            ));
+        };
+        if coefficients.len() <= power {
+            coefficients.resize(power + 1, 0.);
+        }
+        coefficients[power] += if negative { -coefficient } else { coefficient };
+        rest = after;
+    }
+    coefficients.reverse();
+    Ok(coefficients)
+}
+
+/// Parses a single term into its coefficient and power of x, with the coefficient defaulting to
+/// one when there is an x and the power defaulting to one.
+fn parse_term(input: &str) -> IResult<&str, (f64, usize)> {
+    let coefficient = map_res(recognize_float, f64::from_str);
+    let power = preceded(
+        tag("x"),
+        opt(preceded(
+            char('^'),
+            map_res(
+                recognize(take_while1(|char: char| char.is_ascii_digit())),
+                usize::from_str,
+            ),
+        )),
+    )
+    .map(|power| power.unwrap_or(1));
+    let (input, coefficient) = opt(coefficient).parse(input)?;
+    let (input, power) = opt(preceded(opt(char('*')), power)).parse(input)?;
+    match (coefficient, power) {
+        (None, None) => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Float,
+        ))),
+        (coefficient, power) => Ok((input, (coefficient.unwrap_or(1.), power.unwrap_or(0)))),
+    }
+}
+
+/// Parses a number literal with an optional minus sign.
+fn number(input: ParseStream) -> syn::Result<f64> {
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let value = match input.parse()? {
+        Lit::Int(int) => int.base10_parse::<f64>()?,
+        Lit::Float(float) => float.base10_parse::<f64>()?,
+        lit => return Err(Error::new_spanned(lit, "expected a number")),
+    };
+    Ok(if negative { -value } else { value })
+}
+
+/// Parses arms like `x < 0.5 => y = 2x,` ending with a `_ => ...` arm.
+fn parse_piecewise(input: ParseStream) -> syn::Result<Curve> {
+    let mut pieces = Vec::new();
+    loop {
+        let bound = if input.peek(Token![_]) {
+            let _: Token![_] = input.parse()?;
+            f64::INFINITY
+        } else {
+            let x: Ident = input.parse()?;
+            if x != "x" {
+                return Err(Error::new_spanned(x, "expected `x < bound` or `_`"));
+            }
+            let _: Token![<] = input.parse()?;
+            let span = input.span();
+            let bound = number(input)?;
+            if let Some(&(previous, _)) = pieces.last() {
+                if bound <= previous {
+                    return Err(Error::new(
+                        span,
+                        "bounds must increase from one piece to the next",
+                    ));
+                }
+            }
+            bound
+        };
+        let _: Token![=>] = input.parse()?;
+        let tokens = tokens_until(input, |input| input.peek(Token![,]))?;
+        pieces.push((bound, expression(tokens)?));
+        let _: Option<Token![,]> = input.parse()?;
+        if bound == f64::INFINITY {
+            break;
+        }
+        if input.is_empty() {
+            return Err(input.error("expected a final `_ => ...` piece"));
+        }
+    }
+    if !input.is_empty() {
+        return Err(input.error("unexpected piece after `_`"));
+    }
+    Ok(Curve::Piecewise(pieces))
+}
+
+/// Parses control points like `(0, 0), (0.5, 1), (1, 0)` and fits a natural cubic spline through
+/// them, extrapolating the end segments beyond the first and last points.
+fn parse_spline(input: ParseStream) -> syn::Result<Curve> {
+    let mut points = Vec::<(f64, f64)>::new();
+    while !input.is_empty() {
+        let content;
+        let parens = syn::parenthesized!(content in input);
+        let x = number(&content)?;
+        let _: Token![,] = content.parse()?;
+        let y = number(&content)?;
+        if !content.is_empty() {
+            return Err(content.error("expected `(x, y)`"));
+        }
+        if points.last().is_some_and(|&(previous, _)| x <= previous) {
+            return Err(Error::new(
+                parens.span.join(),
+                "control points must have increasing x",
+            ));
+        }
+        points.push((x, y));
+        let _: Option<Token![,]> = input.parse()?;
+    }
+    if points.len() < 2 {
+        return Err(input.error("a spline needs at least two control points"));
+    }
+    Ok(Curve::Piecewise(natural_cubic_spline(&points)))
+}
+
+/// Solves for the second derivatives with them fixed at zero at both ends, then expands each
+/// segment into a polynomial in x.
+fn natural_cubic_spline(points: &[(f64, f64)]) -> Vec<(f64, Vec<f64>)> {
+    let n = points.len() - 1;
+    let h = points
+        .windows(2)
+        .map(|pair| pair[1].0 - pair[0].0)
+        .collect_vec();
+    let slope = |i: usize| (points[i + 1].1 - points[i].1) / h[i];
+    // Thomas algorithm over the interior second derivatives.
+    let mut second = vec![0.; n + 1];
+    let mut diagonal = vec![0.; n + 1];
+    let mut rhs = vec![0.; n + 1];
+    for i in 1..n {
+        diagonal[i] = 2. * (h[i - 1] + h[i]);
+        rhs[i] = 6. * (slope(i) - slope(i - 1));
+        if i > 1 {
+            let factor = h[i - 1] / diagonal[i - 1];
+            diagonal[i] -= factor * h[i - 1];
+            rhs[i] -= factor * rhs[i - 1];
+        }
+    }
+    for i in (1..n).rev() {
+        second[i] = (rhs[i] - h[i] * second[i + 1]) / diagonal[i];
+    }
+    (0..n)
+        .map(|i| {
+            let (x, y) = points[i];
+            let local = [
+                y,
+                slope(i) - h[i] * (2. * second[i] + second[i + 1]) / 6.,
+                second[i] / 2.,
+                (second[i + 1] - second[i]) / (6. * h[i]),
+            ];
+            // Expand the sum of local[k] * (x - x_i)^k.
+            let mut global = [0.; 4];
+            for (k, coefficient) in local.iter().enumerate() {
+                let mut binomial = 1.;
+                for (j, term) in global.iter_mut().enumerate().take(k + 1) {
+                    *term += coefficient * binomial * (-x).powi((k - j) as i32);
+                    binomial *= (k - j) as f64 / (j + 1) as f64;
+                }
+            }
+            let bound = if i + 1 == n {
+                f64::INFINITY
+            } else {
+                points[i + 1].0
+            };
+            (bound, global.into_iter().rev().collect())
+        })
+        .collect()
+}
+
+fn polynomial_tokens(coefficients: &[f64], len: usize) -> TokenStream2 {
+    let padding = len - coefficients.len();
+    let coefficients = std::iter::repeat_n(0., padding)
+        .chain(coefficients.iter().copied())
+        .map(|coefficient| coefficient as f32);
+    quote! { Polynomial([#( #coefficients ),*]) }
+}
+
+fn polynomial_string(coefficients: &[f64]) -> String {
+    coefficients
+        .iter()
+        .rev()
+        .enumerate()
+        .rev()
+        .map(|(exponent, coefficient)| format!("{}x^{exponent}", *coefficient as f32))
+        .join("+")
+}
+
+impl Curve {
+    /// The type of the generated value, for `poly_consts!`.
+    pub fn type_tokens(&self) -> TokenStream2 {
+        match self {
+            Self::Polynomial(coefficients) => {
+                let len = coefficients.len();
+                quote! { Polynomial<#len> }
+            }
+            Self::Piecewise(pieces) => {
+                let count = pieces.len();
+                let len = Self::piece_len(pieces);
+                quote! { Piecewise<#count, #len> }
+            }
+        }
+    }
+
+    fn piece_len(pieces: &[(f64, Vec<f64>)]) -> usize {
+        pieces
+            .iter()
+            .map(|(_, coefficients)| coefficients.len())
+            .max()
+            .unwrap_or(1)
+    }
+
+    pub fn value_tokens(&self) -> TokenStream2 {
+        match self {
+            Self::Polynomial(coefficients) => polynomial_tokens(coefficients, coefficients.len()),
+            Self::Piecewise(pieces) => {
+                let len = Self::piece_len(pieces);
+                let bounds = pieces.iter().map(|&(bound, _)| {
+                    if bound == f64::INFINITY {
+                        quote!(f32::INFINITY)
+                    } else {
+                        let bound = bound as f32;
+                        quote!(#bound)
+                    }
+                });
+                let pieces = pieces
+                    .iter()
+                    .map(|(_, coefficients)| polynomial_tokens(coefficients, len));
+                quote! { Piecewise { bounds: [#( #bounds ),*], pieces: [#( #pieces ),*] } }
+            }
+        }
+    }
+
+    /// The curve as a GeoGebra command.
+    pub fn geogebra(&self) -> String {
+        match self {
+            Self::Polynomial(coefficients) => polynomial_string(coefficients),
+            Self::Piecewise(pieces) => {
+                pieces
+                    .iter()
+                    .rev()
+                    .fold(String::new(), |otherwise, (bound, coefficients)| {
+                        let piece = polynomial_string(coefficients);
+                        if otherwise.is_empty() {
+                            piece
+                        } else {
+                            format!("If(x<{},{piece},{otherwise})", *bound as f32)
+                        }
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(input: &str) -> Curve {
+        syn::parse_str(input).unwrap()
+    }
+
+    fn polynomial(input: &str) -> Vec<f64> {
+        let Curve::Polynomial(coefficients) = curve(input) else {
+            panic!("`{input}` is not a polynomial");
+        };
+        coefficients
+    }
+
+    fn pieces(input: &str) -> Vec<(f64, Vec<f64>)> {
+        let Curve::Piecewise(pieces) = curve(input) else {
+            panic!("`{input}` is not piecewise");
+        };
+        pieces
+    }
+
+    fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+        coefficients
+            .iter()
+            .fold(0., |total, coefficient| total * x + coefficient)
+    }
+
+    /// The error message, and the part of `input` it points at.
+    fn error(input: &str) -> (String, &str) {
+        let Err(error) = syn::parse_str::<Curve>(input) else {
+            panic!("`{input}` parsed");
+        };
+        let span = error.span();
+        (
+            error.to_string(),
+            &input[span.start().column..span.end().column],
+        )
+    }
+
+    #[test]
+    fn polynomial_terms() {
+        assert_eq!(polynomial("3x^2 - x + 4"), [3., -1., 4.]);
+        assert_eq!(polynomial("-x^3"), [-1., 0., 0., 0.]);
+        assert_eq!(polynomial("2 * x"), [2., 0.]);
+        assert_eq!(polynomial("1.5"), [1.5]);
+        assert_eq!(polynomial("y = x + 1"), [1., 1.]);
+        assert_eq!(polynomial("x + 2x - 0.5x^2 + x^2"), [0.5, 3., 0.]);
+    }
+
+    #[test]
+    fn scientific_notation() {
+        assert_eq!(polynomial("-2.5e-3x^2 + 1e2"), [-2.5e-3, 0., 100.]);
+    }
+
+    #[test]
+    fn piecewise_bounds() {
+        let pieces = pieces("piecewise { x < -1 => 0, x < 1.5 => y = 2x, _ => 3 }");
+        assert_eq!(
+            pieces,
+            [
+                (-1., vec![0.]),
+                (1.5, vec![2., 0.]),
+                (f64::INFINITY, vec![3.])
+            ]
+        );
+    }
+
+    #[test]
+    fn spline_passes_through_points_smoothly() {
+        let points = [(0., 0.), (0.4, 0.25), (1., 1.), (2., 0.5)];
+        let pieces = pieces("spline [(0, 0), (0.4, 0.25), (1, 1), (2, 0.5)]");
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces.last().unwrap().0, f64::INFINITY);
+        for (i, (_, coefficients)) in pieces.iter().enumerate() {
+            assert!((evaluate(coefficients, points[i].0) - points[i].1).abs() < 1e-9);
+            assert!((evaluate(coefficients, points[i + 1].0) - points[i + 1].1).abs() < 1e-9);
+        }
+        let slope = |coefficients: &[f64], x: f64| {
+            (evaluate(coefficients, x + 1e-6) - evaluate(coefficients, x - 1e-6)) / 2e-6
+        };
+        for pair in pieces.windows(2) {
+            let x = pair[0].0;
+            assert!((slope(&pair[0].1, x) - slope(&pair[1].1, x)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn two_point_spline_is_a_line() {
+        let pieces = pieces("spline [(0, 1), (2, 5)]");
+        let [(bound, coefficients)] = &pieces[..] else {
+            panic!("expected one piece");
+        };
+        assert_eq!(*bound, f64::INFINITY);
+        assert_eq!(evaluate(coefficients, 1.), 3.);
+        assert_eq!(evaluate(coefficients, 3.), 7.);
+    }
+
+    #[test]
+    fn geogebra() {
+        assert_eq!(curve("3x^2 - 1").geogebra(), "3x^2+0x^1+-1x^0");
+        assert_eq!(
+            curve("piecewise { x < 0 => 0, x < 1 => x, _ => 1 }").geogebra(),
+            "If(x<0,0x^0,If(x<1,1x^1+0x^0,1x^0))"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("x + - 2"),
+            ("expected a term after this sign".into(), "+ -")
+        );
+        assert_eq!(
+            error("x +"),
+            ("expected a term after this sign".into(), "+")
+        );
+        assert_eq!(
+            error("x^2 + sin x"),
+            (
+                "invalid term `sinx`: expected a number, `x`, or `x^n` with a coefficient".into(),
+                "sin x"
+            )
+        );
+        assert_eq!(
+            error("piecewise { x < 1 => 0, x < 1 => 1, _ => 2 }"),
+            (
+                "bounds must increase from one piece to the next".into(),
+                "1"
+            )
+        );
+        assert_eq!(
+            error("piecewise { x < 1 => 0 }"),
+            (
+                "unexpected end of input, expected a final `_ => ...` piece".into(),
+                "}"
+            )
+        );
+        assert_eq!(
+            error("spline [(0, 0), (1, 1), (1, 2)]"),
+            ("control points must have increasing x".into(), "(1, 2)")
+        );
+        assert_eq!(
+            error("spline [(0, 0)]"),
+            (
+                "unexpected end of input, a spline needs at least two control points".into(),
+                "]"
+            )
+        );
+        assert_eq!(
+            error("pieces { _ => 0 }"),
+            ("expected `piecewise`".into(), "pieces")
+        );
+        assert_eq!(
+            error("splines [(0, 0), (1, 1)]"),
+            ("expected `spline`".into(), "splines")
+        );
+    }
+}
@@ -1,187 +1,391 @@
-#![feature(proc_macro_expand)]
+use curve::Curve;
 use itertools::Itertools;
-use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_until, take_while},
-    combinator::{all_consuming, eof, not, opt, rest},
-    error::Error as NomError,
-    multi::many1,
-    sequence::{preceded, tuple},
-    Parser,
-};
 use proc_macro::TokenStream;
-use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::quote;
-use std::{collections::HashMap, str::FromStr};
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use retro_wicket_aseprite::{Aseprite, Direction};
+use retro_wicket_palette::Entry;
+use std::{env::var, fs::read};
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input, Error, Expr, ExprRange, Ident, LitStr, Token,
+    parse_macro_input,
+    punctuated::Punctuated,
+    Attribute, Error, Ident, Lit, LitStr, Token, Visibility,
 };
-use syn::{ExprLit, Lit, LitInt};
 use urlencoding::encode;
 
+mod css;
+mod curve;
+
+/// Builds an `Animation` from the frames and tags of `src/sprites/<name>.aseprite`, whose frames
+/// the build script exports as textures.
+#[proc_macro]
+pub fn include_animation(input: TokenStream) -> TokenStream {
+    let name = parse_macro_input!(input as LitStr);
+    let path = format!(
+        "{}/src/sprites/{}.aseprite",
+        var("CARGO_MANIFEST_DIR").unwrap(),
+        name.value()
+    );
+    let aseprite = match read(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| Aseprite::parse(&bytes).map_err(|error| error.to_string()))
+    {
+        Ok(aseprite) => aseprite,
+        Err(error) => {
+            return Error::new_spanned(name, format!("failed to load {path}: {error}"))
+                .to_compile_error()
+                .into()
+        }
+    };
+    let frames = if aseprite.frames.len() == 1 {
+        vec![name.value()]
+    } else {
+        (1..=aseprite.frames.len())
+            .map(|suffix| format!("{}{suffix}", name.value()))
+            .collect()
+    };
+    let durations = aseprite
+        .frames
+        .iter()
+        .map(|frame| frame.duration as f32 / 1000.);
+    let mut tags = Vec::new();
+    for tag in &aseprite.tags {
+        let tag_name = &tag.name;
+        let from = tag.from;
+        let to = tag.to;
+        let direction = match tag.direction {
+            Direction::Forward => quote!(Forward),
+            Direction::PingPong => quote!(PingPong),
+            direction => {
+                return Error::new_spanned(
+                    &name,
+                    format!(
+                        "tag {tag_name:?} plays {direction:?}, but only forward and ping-pong \
+                         tags are supported"
+                    ),
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        let playback = match tag.repeat {
+            0 => quote!(Playback::Loop),
+            repeat => quote!(Playback::Repeat(#repeat)),
+        };
+        tags.push(quote! {
+            AnimationTag {
+                name: #tag_name,
+                from: #from,
+                to: #to,
+                direction: AnimationDirection::#direction,
+                playback: #playback,
+            }
+        });
+    }
+    quote! {{
+        const _: &[u8] = include_bytes!(#path);
+        Animation::new(
+            vec![#( include_texture!(#frames) ),*],
+            vec![#( #durations ),*],
+            vec![#( #tags ),*],
+        )
+    }}
+    .into()
+}
+
+/// Parses a colour as `rgb`, `rgba`, `rrggbb` or `rrggbbaa` hexadecimal digits, optionally after a
+/// `#` or inside a string, or as a CSS colour name, into red, green, blue and alpha bytes.
+fn parse_colour(input: TokenStream2) -> syn::Result<[u8; 4]> {
+    let mut tokens = input.into_iter().peekable();
+    let hash = tokens
+        .next_if(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == '#'))
+        .is_some();
+    let Some(token) = tokens.next() else {
+        return Err(Error::new(
+            Span::call_site(),
+            "expected hexadecimal digits or a CSS colour name",
+        ));
+    };
+    if let Some(extra) = tokens.next() {
+        return Err(Error::new(
+            extra.span(),
+            "unexpected token after the colour",
+        ));
+    }
+    let span = token.span();
+    let (text, hash) = match token {
+        TokenTree::Ident(ident) => (ident.to_string(), hash),
+        TokenTree::Literal(literal) => match Lit::new(literal) {
+            Lit::Str(string) if !hash => {
+                let value = string.value();
+                match value.trim().strip_prefix('#') {
+                    Some(digits) => (digits.to_string(), true),
+                    None => (value.trim().to_string(), false),
+                }
+            }
+            literal => (literal.to_token_stream().to_string(), hash),
+        },
+        _ => {
+            return Err(Error::new(
+                span,
+                "expected hexadecimal digits or a CSS colour name",
+            ))
+        }
+    };
+    if !hash {
+        let name = text.to_ascii_lowercase();
+        if name == "transparent" {
+            return Ok([0; 4]);
+        }
+        if let Some(&(_, colour)) = css::NAMED_COLOURS.iter().find(|(css, _)| *css == name) {
+            let [_, red, green, blue] = colour.to_be_bytes();
+            return Ok([red, green, blue, u8::MAX]);
+        }
+    }
+    if let Some(invalid) = text.chars().find(|char| !char.is_ascii_hexdigit()) {
+        return Err(Error::new(
+            span,
+            if hash {
+                format!("`{invalid}` is not a hexadecimal digit")
+            } else {
+                format!(
+                    "`{text}` is not a CSS colour name, and `{invalid}` is not a hexadecimal digit"
+                )
+            },
+        ));
+    }
+    let digits = text
+        .chars()
+        .map(|char| char.to_digit(16).unwrap() as u8)
+        .collect_vec();
+    let channels = match digits.len() {
+        3 | 4 => digits.iter().map(|digit| digit * 0x11).collect_vec(),
+        6 | 8 => digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect_vec(),
+        len => {
+            return Err(Error::new(
+                span,
+                format!("expected 3, 4, 6 or 8 hexadecimal digits, found {len}"),
+            ))
+        }
+    };
+    Ok(match channels[..] {
+        [red, green, blue] => [red, green, blue, u8::MAX],
+        [red, green, blue, alpha] => [red, green, blue, alpha],
+        _ => unreachable!(),
+    })
+}
+
+/// A `macroquad` `Color` from a colour in any form `parse_colour` accepts, like `hex!(#e4a672)`,
+/// `hex!(fff8)` or `hex!(rebeccapurple)`.
+#[proc_macro]
+pub fn hex(input: TokenStream) -> TokenStream {
+    match parse_colour(input.into()) {
+        Ok([red, green, blue, alpha]) => quote! {
+            macroquad::color_u8!(#red, #green, #blue, #alpha)
+        },
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
+
+/// Generates a `Copy` enum with a variant per colour of a `.hex` or `.gpl` palette in `src`, along
+/// with `colour()`, `ALL`, `iter()` and a `Display` that writes the variant name.
+///
+/// ```ignore
+/// palette! {
+///     enum Colour in "palettes/endesga-16.hex" { Birch, Oak, ... }
+/// }
+/// ```
+///
+/// The variant list can be left out for `.gpl` files with a name on every colour.
 #[proc_macro]
-pub fn include_textures(input: TokenStream) -> TokenStream {
+pub fn palette(input: TokenStream) -> TokenStream {
     struct Input {
-        name: String,
-        start: usize,
-        end_inclusive: usize,
+        attrs: Vec<Attribute>,
+        visibility: Visibility,
+        name: Ident,
+        path: LitStr,
+        variants: Option<Vec<Ident>>,
     }
 
     impl Parse for Input {
         fn parse(input: ParseStream) -> syn::Result<Self> {
-            let name: LitStr = input.parse()?;
-            let _: Token![,] = input.parse()?;
-            let range: ExprRange = input.parse()?;
-            let Some(ref start) = range.start else {
-                return Err(Error::new_spanned(
-                    range,
-                    "expected range to have a start limit",
-                ));
-            };
-            let Expr::Lit(ExprLit {
-                lit: Lit::Int(ref start @ LitInt { .. }),
-                ..
-            }) = **start
-            else {
-                return Err(Error::new_spanned(
-                    start,
-                    "expected range start to be an integer literal",
-                ));
-            };
-            let start = start.base10_parse()?;
-
-            let Some(ref end_inclusive) = range.end else {
-                return Err(Error::new_spanned(
-                    range,
-                    "expected range to have an end limit",
-                ));
-            };
-            let Expr::Lit(ExprLit {
-                lit: Lit::Int(ref end_inclusive @ LitInt { .. }),
-                ..
-            }) = **end_inclusive
-            else {
-                return Err(Error::new_spanned(
-                    end_inclusive,
-                    "expected range end to be an integer literal",
-                ));
+            let attrs = input.call(Attribute::parse_outer)?;
+            let visibility = input.parse()?;
+            let _: Token![enum] = input.parse()?;
+            let name = input.parse()?;
+            let _: Token![in] = input.parse()?;
+            let path = input.parse()?;
+            let variants = if input.is_empty() {
+                None
+            } else {
+                let content;
+                syn::braced!(content in input);
+                Some(
+                    Punctuated::<Ident, Token![,]>::parse_terminated(&content)?
+                        .into_iter()
+                        .collect(),
+                )
             };
-            let end_inclusive = end_inclusive.base10_parse()?;
-
             Ok(Self {
-                name: name.value(),
-                start,
-                end_inclusive,
+                attrs,
+                visibility,
+                name,
+                path,
+                variants,
             })
         }
     }
 
     let Input {
+        attrs,
+        visibility,
         name,
-        start,
-        end_inclusive,
+        path,
+        variants,
     } = parse_macro_input!(input as Input);
-    let tokens: TokenStream2 = (start..=end_inclusive)
-        .map(|suffix| {
-            let name = format!("{name}{suffix}");
-            quote! {
-                include_texture!(#name),
-            }
-        })
-        .collect();
-    let tokens = quote! { [#tokens] };
-    tokens.into()
-}
-
-#[proc_macro]
-pub fn hex(input: TokenStream) -> TokenStream {
-    let to_string = input.to_string();
-    u32::from_str_radix(&to_string, 16)
-        .ok()
-        .filter(|_| to_string.len() == 6)
-        .map_or_else(
-            || {
-                Error::new_spanned(
-                    TokenStream2::from(input),
-                    "expected a 6 digit hexadecimal number without a #",
+    let full_path = format!(
+        "{}/src/{}",
+        var("CARGO_MANIFEST_DIR").unwrap(),
+        path.value()
+    );
+    let source = match read(&full_path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(error) => {
+            return Error::new_spanned(path, format!("failed to read {full_path}: {error}"))
+                .to_compile_error()
+                .into()
+        }
+    };
+    let palette = if path.value().ends_with(".gpl") {
+        retro_wicket_palette::parse_gpl(&source)
+    } else if path.value().ends_with(".hex") {
+        retro_wicket_palette::parse_hex(&source)
+    } else {
+        return Error::new_spanned(path, "expected a .hex or .gpl palette")
+            .to_compile_error()
+            .into();
+    };
+    let entries = match palette {
+        Ok(palette) => palette.entries,
+        Err(error) => {
+            return Error::new_spanned(&path, format!("{}: {error}", path.value()))
+                .to_compile_error()
+                .into()
+        }
+    };
+    let variants = match variants {
+        Some(variants) if variants.len() == entries.len() => variants,
+        Some(variants) => {
+            return Error::new_spanned(
+                &path,
+                format!(
+                    "{} has {} colours but {} variants were given",
+                    path.value(),
+                    entries.len(),
+                    variants.len()
+                ),
+            )
+            .to_compile_error()
+            .into()
+        }
+        None => match entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                entry
+                    .name
+                    .as_deref()
+                    .and_then(|name| syn::parse_str::<Ident>(name).ok())
+                    .ok_or(index)
+            })
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(variants) => variants,
+            Err(index) => {
+                return Error::new_spanned(
+                    &path,
+                    format!(
+                        "colour {} of {} has no name that is a valid identifier, so list the \
+                         variants explicitly",
+                        index + 1,
+                        path.value()
+                    ),
                 )
                 .to_compile_error()
-            },
-            |colour| {
-                let [_, red, green, blue] = colour.to_be_bytes();
-                quote! {
-                    macroquad::color_u8!(#red, #green, #blue, 255)
+                .into()
+            }
+        },
+    };
+    let len = variants.len();
+    let docs = entries.iter().map(
+        |Entry {
+             rgb: [red, green, blue],
+             ..
+         }| { format!("`#{red:02x}{green:02x}{blue:02x}`") },
+    );
+    let colours = entries.iter().map(
+        |Entry {
+             rgb: [red, green, blue],
+             ..
+         }| { quote!(macroquad::color_u8!(#red, #green, #blue, 255)) },
+    );
+    let names = variants.iter().map(ToString::to_string);
+    quote! {
+        const _: &[u8] = include_bytes!(#full_path);
+
+        #(#attrs)*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #visibility enum #name {
+            #( #[doc = #docs] #variants, )*
+        }
+
+        #[allow(dead_code)]
+        impl #name {
+            #visibility const ALL: [Self; #len] = [#( Self::#variants ),*];
+
+            #visibility const fn colour(self) -> macroquad::color::Color {
+                match self {
+                    #( Self::#variants => #colours, )*
                 }
-            },
-        )
-        .into()
-}
+            }
 
-#[proc_macro]
-pub fn poly(input: TokenStream) -> TokenStream {
-    fn inner(input: &TokenStream) -> Option<TokenStream> {
-        let input = input.to_string().replace(' ', "");
-        let terms = terms(&input)?;
-        Some(
-            quote! {
-                Polynomial([#( #terms ),*])
+            #visibility fn iter() -> impl Iterator<Item = Self> {
+                Self::ALL.into_iter()
             }
-            .into(),
-        )
+        }
+
+        impl std::fmt::Display for #name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(match self {
+                    #( Self::#variants => #names, )*
+                })
+            }
+        }
     }
-    inner(&input).unwrap_or_else(|| {
-        Error::new_spanned(TokenStream2::from(input), "expected a valid polynomial")
-            .to_compile_error()
-            .into()
-    })
+    .into()
 }
 
-fn terms(input: &str) -> Option<Vec<f32>> {
-    let coefficient = preceded(
-        not(eof),
-        alt((take_until("x"), rest::<&str, NomError<_>>))
-            .map(f32::from_str)
-            .map(Result::ok)
-            .map(|coefficient| coefficient.unwrap_or(1.)),
-    );
-    let term = tuple((
-        coefficient,
-        opt(preceded(
-            tag("x"),
-            opt(preceded(
-                tag("^"),
-                take_while(|char: char| char.is_ascii_digit())
-                    .map(u32::from_str)
-                    .map(Result::ok),
-            ))
-            .map(Option::flatten)
-            .map(|exponent| exponent.unwrap_or(1)),
-        ))
-        .map(|exponent| exponent.unwrap_or(0)),
-    ))
-    .map(|(coefficient, exponent)| (exponent, coefficient));
-    let mut polynomial = all_consuming(preceded(tag("y="), many1(term)));
-    let terms = polynomial(input)
-        .ok()?
-        .1
-        .into_iter()
-        .collect::<HashMap<_, _>>();
-    let terms = (0..=terms.keys().max().copied()?)
-        .map(|exponent| terms.get(&exponent).unwrap_or(&0.))
-        .rev()
-        .copied()
-        .collect_vec();
-    Some(terms)
+/// A curve from an expression like `y = -0.5x^3 + 2.1e-2x - 1`,
+/// `piecewise { x < 0 => y = 0, _ => y = x^2 }` or `spline [(0, 0), (0.5, 1), (1, 0)]`.
+#[proc_macro]
+pub fn poly(input: TokenStream) -> TokenStream {
+    let curve = parse_macro_input!(input as Curve);
+    curve.value_tokens().into()
 }
 
+/// Constants like `const NAME => curve;` for each curve `poly!` accepts, with docs linking to a
+/// graph of the curve.
 #[proc_macro]
 pub fn poly_consts(input: TokenStream) -> TokenStream {
     struct Input(Vec<Const>);
-    #[derive(Debug)]
     struct Const {
-        name: String,
-        terms: Vec<f32>,
+        name: Ident,
+        curve: Curve,
     }
 
     impl Parse for Input {
@@ -194,38 +398,28 @@ pub fn poly_consts(input: TokenStream) -> TokenStream {
     impl Parse for Const {
         fn parse(input: ParseStream) -> syn::Result<Self> {
             let _const: Token![const] = input.parse()?;
-            let name: Ident = input.parse()?;
+            let name = input.parse()?;
             let _arrow: Token![=>] = input.parse()?;
-            let tokens: TokenStream2 = input.parse()?;
-            Ok(Self {
-                name: name.to_string(),
-                terms: terms(&tokens.to_string().replace(' ', ""))
-                    .ok_or_else(|| Error::new_spanned(tokens, "expected a valid polynomial"))?,
-            })
+            let curve = input.parse()?;
+            Ok(Self { name, curve })
         }
     }
 
     let Input(consts) = parse_macro_input!(input as Input);
     let consts = consts
         .into_iter()
-        .map(|Const { name, terms }| {
-            let len = terms.len();
-            let name = Ident::new(&name, Span::call_site());
-            let string = terms
-                .iter()
-                .rev()
-                .enumerate()
-                .rev()
-                .map(|(exponent, coefficient)| format!("{coefficient}x^{exponent}"))
-                .join("+");
+        .map(|Const { name, curve }| {
+            let string = curve.geogebra();
             let doc = format!(
-                "Polynomial `{}`. [View in GeoGebra](https://geogebra.org/classic?command=y={})",
+                "Curve `{}`. [View in GeoGebra](https://geogebra.org/classic?command=y={})",
                 &string,
                 encode(&string)
             );
+            let ty = curve.type_tokens();
+            let value = curve.value_tokens();
             quote! {
                 #[doc = #doc]
-                const #name: Polynomial<#len> = Polynomial([#( #terms ),*]);
+                const #name: #ty = #value;
             }
         })
         .collect_vec();
@@ -234,3 +428,92 @@ pub fn poly_consts(input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn colour(input: &str) -> [u8; 4] {
+        parse_colour(TokenStream2::from_str(input).unwrap()).unwrap()
+    }
+
+    /// The error message, and the part of `input` it points at.
+    fn error(input: &str) -> (String, &str) {
+        let error = parse_colour(TokenStream2::from_str(input).unwrap()).unwrap_err();
+        let span = error.span();
+        let text = if input.is_empty() {
+            ""
+        } else {
+            &input[span.start().column..span.end().column]
+        };
+        (error.to_string(), text)
+    }
+
+    #[test]
+    fn hexadecimal_forms() {
+        assert_eq!(colour("#e4a672"), [0xe4, 0xa6, 0x72, 0xff]);
+        assert_eq!(colour("#e4a67280"), [0xe4, 0xa6, 0x72, 0x80]);
+        assert_eq!(colour("#fa0"), [0xff, 0xaa, 0x00, 0xff]);
+        assert_eq!(colour("#fa08"), [0xff, 0xaa, 0x00, 0x88]);
+        assert_eq!(colour("e4a672"), [0xe4, 0xa6, 0x72, 0xff]);
+        // Digits that lex as number literals.
+        assert_eq!(colour("#123456"), [0x12, 0x34, 0x56, 0xff]);
+        assert_eq!(colour("#1e3"), [0x11, 0xee, 0x33, 0xff]);
+        assert_eq!(colour("#0f0"), [0x00, 0xff, 0x00, 0xff]);
+        assert_eq!(colour("\"#e4a672\""), [0xe4, 0xa6, 0x72, 0xff]);
+        assert_eq!(colour("\" fa08 \""), [0xff, 0xaa, 0x00, 0x88]);
+    }
+
+    #[test]
+    fn css_names() {
+        assert_eq!(colour("rebeccapurple"), [0x66, 0x33, 0x99, 0xff]);
+        assert_eq!(colour("RebeccaPurple"), [0x66, 0x33, 0x99, 0xff]);
+        assert_eq!(colour("\"navy\""), [0x00, 0x00, 0x80, 0xff]);
+        assert_eq!(colour("transparent"), [0; 4]);
+        // Names don't take a `#`.
+        assert_eq!(colour("#add"), [0xaa, 0xdd, 0xdd, 0xff]);
+    }
+
+    #[test]
+    fn css_names_are_sorted_lowercase_and_unique() {
+        assert_eq!(css::NAMED_COLOURS.len(), 148);
+        for pair in css::NAMED_COLOURS.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{} before {}", pair[0].0, pair[1].0);
+        }
+        for (name, colour) in css::NAMED_COLOURS {
+            assert_eq!(*name, name.to_ascii_lowercase());
+            assert!(*colour <= 0xff_ffff, "{name}");
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let cases = [
+            ("", "expected hexadecimal digits or a CSS colour name", ""),
+            ("#", "expected hexadecimal digits or a CSS colour name", ""),
+            ("#ggg", "`g` is not a hexadecimal digit", "ggg"),
+            ("#navy", "`n` is not a hexadecimal digit", "navy"),
+            (
+                "notacolour",
+                "`notacolour` is not a CSS colour name, and `n` is not a hexadecimal digit",
+                "notacolour",
+            ),
+            (
+                "#e4a67",
+                "expected 3, 4, 6 or 8 hexadecimal digits, found 5",
+                "e4a67",
+            ),
+            ("#fff fff", "unexpected token after the colour", "fff"),
+            ("#fff, 1", "unexpected token after the colour", ","),
+            (
+                "[fff]",
+                "expected hexadecimal digits or a CSS colour name",
+                "[fff]",
+            ),
+        ];
+        for (input, message, text) in cases {
+            assert_eq!(error(input), (message.to_string(), text), "{input:?}");
+        }
+    }
+}
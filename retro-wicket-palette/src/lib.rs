@@ -0,0 +1,176 @@
+//! Parsers for `.hex` and `.gpl` palette files, shared by `palette!` and the palettes loaded at
+//! runtime so both read a file the same way.
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    MissingHeader,
+    Colour { line: usize, text: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "missing \"GIMP Palette\" header"),
+            Self::Colour { line, text } => write!(f, "invalid colour {text:?} on line {line}"),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+/// A colour read from a palette file, with its name if the file gave it one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: Option<String>,
+    pub rgb: [u8; 3],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Palette {
+    /// From the `Name:` header of a GIMP palette.
+    pub name: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+/// Parses one `rrggbb` colour per line, as exported by Lospec, ignoring blank lines.
+pub fn parse_hex(source: &str) -> Result<Palette, Error> {
+    let entries = source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let text = line.trim();
+            u32::from_str_radix(text, 16)
+                .ok()
+                .filter(|_| text.len() == 6)
+                .map(|colour| {
+                    let [_, red, green, blue] = colour.to_be_bytes();
+                    Entry {
+                        name: None,
+                        rgb: [red, green, blue],
+                    }
+                })
+                .ok_or_else(|| Error::Colour {
+                    line: index + 1,
+                    text: text.to_string(),
+                })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Palette {
+        name: None,
+        entries,
+    })
+}
+
+/// Parses a GIMP palette: a `GIMP Palette` header, optional `Name:` and `Columns:` lines and `#`
+/// comments, then one `red green blue name` line per colour.
+pub fn parse_gpl(source: &str) -> Result<Palette, Error> {
+    let mut lines = source.lines().enumerate();
+    if lines.next().map(|(_, line)| line.trim()) != Some("GIMP Palette") {
+        return Err(Error::MissingHeader);
+    }
+    let mut palette = Palette::default();
+    for (index, line) in lines {
+        let text = line.trim();
+        if let Some(name) = text.strip_prefix("Name:") {
+            palette.name = Some(name.trim().to_string());
+            continue;
+        }
+        if text.is_empty() || text.starts_with('#') || text.starts_with("Columns:") {
+            continue;
+        }
+        let mut words = text.split_whitespace();
+        let channels = words
+            .by_ref()
+            .take(3)
+            .map(str::parse)
+            .collect::<Result<Vec<u8>, _>>();
+        let Ok(&[red, green, blue]) = channels.as_deref() else {
+            return Err(Error::Colour {
+                line: index + 1,
+                text: text.to_string(),
+            });
+        };
+        let name = words.collect::<Vec<_>>().join(" ");
+        palette.entries.push(Entry {
+            name: (!name.is_empty()).then_some(name),
+            rgb: [red, green, blue],
+        });
+    }
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: Option<&str>, rgb: [u8; 3]) -> Entry {
+        Entry {
+            name: name.map(str::to_string),
+            rgb,
+        }
+    }
+
+    #[test]
+    fn hex() {
+        let palette = parse_hex("e4a672\n\n  0099DB \n").unwrap();
+        assert_eq!(palette.name, None);
+        assert_eq!(
+            palette.entries,
+            [
+                entry(None, [0xe4, 0xa6, 0x72]),
+                entry(None, [0x00, 0x99, 0xdb])
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_errors() {
+        for (source, line, text) in [("e4a672\nfa0", 2, "fa0"), ("#e4a672", 1, "#e4a672")] {
+            assert_eq!(
+                parse_hex(source),
+                Err(Error::Colour {
+                    line,
+                    text: text.to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn gpl() {
+        let source = "GIMP Palette\nName: Dusk\nColumns: 2\n# A comment\n\n228 166 114 Sand Dune\n  0 153 219\n";
+        let palette = parse_gpl(source).unwrap();
+        assert_eq!(palette.name.as_deref(), Some("Dusk"));
+        assert_eq!(
+            palette.entries,
+            [
+                entry(Some("Sand Dune"), [228, 166, 114]),
+                entry(None, [0, 153, 219])
+            ]
+        );
+    }
+
+    #[test]
+    fn gpl_errors() {
+        assert_eq!(parse_gpl("Name: Dusk\n0 0 0"), Err(Error::MissingHeader));
+        assert_eq!(
+            parse_gpl("GIMP Palette\n0 0 0\n256 0 0 Too red"),
+            Err(Error::Colour {
+                line: 3,
+                text: "256 0 0 Too red".to_string()
+            })
+        );
+        assert_eq!(
+            parse_gpl("GIMP Palette\n0 0"),
+            Err(Error::Colour {
+                line: 2,
+                text: "0 0".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use gilrs::{Button, EventType, Gilrs};
+use macroquad::{
+    input::{
+        is_key_down, is_key_pressed, is_mouse_button_pressed, is_mouse_button_released,
+        mouse_delta_position, KeyCode, MouseButton,
+    },
+    math::{Rect, Vec2},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonState {
+    Idle,
+    Focused,
+    Pressed,
+}
+
+/// One frame of menu input. Keyboard and gamepad navigation move focus through the buttons in the
+/// order they were laid out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavigationInput {
+    pub previous: bool,
+    pub next: bool,
+    pub activate: bool,
+    pub mouse: Vec2,
+    pub mouse_moved: bool,
+    pub mouse_pressed: bool,
+    pub mouse_released: bool,
+}
+
+impl NavigationInput {
+    pub fn read(mouse: Vec2, gamepads: &Gamepads) -> Self {
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let tab = is_key_pressed(KeyCode::Tab);
+        Self {
+            previous: is_key_pressed(KeyCode::Left)
+                || is_key_pressed(KeyCode::Up)
+                || (tab && shift)
+                || gamepads.pressed(Button::DPadLeft)
+                || gamepads.pressed(Button::DPadUp),
+            next: is_key_pressed(KeyCode::Right)
+                || is_key_pressed(KeyCode::Down)
+                || (tab && !shift)
+                || gamepads.pressed(Button::DPadRight)
+                || gamepads.pressed(Button::DPadDown),
+            activate: is_key_pressed(KeyCode::Enter)
+                || is_key_pressed(KeyCode::KpEnter)
+                || is_key_pressed(KeyCode::Space)
+                || gamepads.pressed(Button::South),
+            mouse,
+            mouse_moved: mouse_delta_position() != Vec2::ZERO,
+            mouse_pressed: is_mouse_button_pressed(MouseButton::Left),
+            mouse_released: is_mouse_button_released(MouseButton::Left),
+        }
+    }
+}
+
+/// Every connected gamepad, merged into one. Events are drained once a frame so a press counts on
+/// exactly one frame, whichever screen is showing.
+pub struct Gamepads {
+    /// `None` if gamepad support couldn't start, such as without udev on Linux.
+    gilrs: Option<Gilrs>,
+    pressed: Vec<Button>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Self {
+            gilrs: Gilrs::new()
+                .map_err(|error| eprintln!("gamepads are unavailable: {error}"))
+                .ok(),
+            pressed: Vec::new(),
+        }
+    }
+
+    /// Collects the buttons pressed since the last frame.
+    pub fn update(&mut self) {
+        self.pressed.clear();
+        let Some(gilrs) = &mut self.gilrs else {
+            return;
+        };
+        while let Some(event) = gilrs.next_event() {
+            if let EventType::ButtonPressed(button, _) = event.event {
+                self.pressed.push(button);
+            }
+        }
+    }
+
+    pub fn pressed(&self, button: Button) -> bool {
+        self.pressed.contains(&button)
+    }
+}
+
+/// Focus and press state for the buttons on the current screen, which are identified by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Buttons {
+    focused: Option<usize>,
+    pressed: Option<usize>,
+}
+
+impl Buttons {
+    /// Hit-tests and navigates `buttons`, given in focus order, returning the id of the button that
+    /// was activated this frame, if any.
+    pub fn update(&mut self, buttons: &[(usize, Rect)], input: NavigationInput) -> Option<usize> {
+        let hovered = buttons
+            .iter()
+            .find(|(_, rect)| rect.contains(input.mouse))
+            .map(|&(id, _)| id);
+        if hovered.is_some() && (input.mouse_moved || self.focused.is_none()) {
+            self.focused = hovered;
+        }
+        let focused_index = self
+            .focused
+            .and_then(|focused| buttons.iter().position(|&(id, _)| id == focused));
+        if input.previous || input.next || (input.activate && focused_index.is_none()) {
+            let len = buttons.len();
+            let index = match (focused_index, input.previous) {
+                _ if len == 0 => None,
+                (None, _) => Some(0),
+                (Some(index), true) => Some((index + len - 1) % len),
+                (Some(index), false) => Some((index + 1) % len),
+            };
+            self.focused = index.map(|index| buttons[index].0);
+            return None;
+        }
+        if input.activate {
+            return self.focused;
+        }
+        if input.mouse_pressed {
+            self.pressed = hovered;
+        }
+        if input.mouse_released {
+            let pressed = self.pressed.take();
+            if pressed.is_some() && pressed == hovered {
+                return pressed;
+            }
+        }
+        None
+    }
+
+    pub fn state(&self, id: usize) -> ButtonState {
+        if self.pressed == Some(id) {
+            ButtonState::Pressed
+        } else if self.focused == Some(id) {
+            ButtonState::Focused
+        } else {
+            ButtonState::Idle
+        }
+    }
+}
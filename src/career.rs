@@ -0,0 +1,654 @@
+use crate::{
+    layout::{Anchor, Node},
+    shot::Timing,
+    tournament::{PLAYER, TEAM_NAMES},
+    tuning::{ShotTuning, Tuning},
+    Colour, Competition, Game, State, Teams,
+};
+use macroquad::math::vec2;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, write},
+    io::ErrorKind,
+    iter,
+};
+use strum::{Display, VariantArray};
+
+/// Where the career profile is saved, relative to where the game is run from.
+const PATH: &str = "career.toml";
+
+/// How many matches there are in a season.
+const SEASON_LENGTH: usize = 6;
+/// The most points that can be put into one attribute.
+pub const MAX_ATTRIBUTE: u32 = 5;
+
+const XP_PER_RUN: u32 = 2;
+const XP_PER_PERFECT: u32 = 10;
+const XP_PER_GOOD: u32 = 4;
+const XP_FOR_WIN: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, VariantArray)]
+pub enum Attribute {
+    /// Widens every shot's timing window.
+    Timing,
+    /// Hits the ball harder.
+    Power,
+    /// Middles the ball more often, making edges rarer.
+    Accuracy,
+}
+
+impl Attribute {
+    /// How much each point improves the attribute, as a fraction of the base value.
+    const fn per_point(self) -> f32 {
+        match self {
+            Self::Timing => 0.1,
+            Self::Power => 0.08,
+            Self::Accuracy => -0.15,
+        }
+    }
+}
+
+/// Something to play with, unlocked on reaching a level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bat {
+    pub name: &'static str,
+    pub level: u32,
+    pub colour: Colour,
+    /// Multiplies shot power.
+    pub power: f32,
+    /// Multiplies shot timing windows.
+    pub timing_window: f32,
+}
+
+pub const BATS: [Bat; 4] = [
+    Bat {
+        name: "Willow",
+        level: 1,
+        colour: Colour::Fabric,
+        power: 1.,
+        timing_window: 1.,
+    },
+    Bat {
+        name: "Cannon",
+        level: 3,
+        colour: Colour::Darkbark,
+        power: 1.1,
+        timing_window: 0.95,
+    },
+    Bat {
+        name: "Feather",
+        level: 5,
+        colour: Colour::Birch,
+        power: 0.95,
+        timing_window: 1.15,
+    },
+    Bat {
+        name: "Golden",
+        level: 8,
+        colour: Colour::Candle,
+        power: 1.1,
+        timing_window: 1.1,
+    },
+];
+
+/// What the batter wears, which tints their sprite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kit {
+    pub name: &'static str,
+    pub level: u32,
+    pub colour: Colour,
+}
+
+pub const KITS: [Kit; 4] = [
+    Kit {
+        name: "Whites",
+        level: 1,
+        colour: Colour::White,
+    },
+    Kit {
+        name: "Ocean",
+        level: 2,
+        colour: Colour::Ion,
+    },
+    Kit {
+        name: "Forest",
+        level: 4,
+        colour: Colour::Flora,
+    },
+    Kit {
+        name: "Ember",
+        level: 6,
+        colour: Colour::Blood,
+    },
+];
+
+/// How the player batted in a career match, and the XP it earned them. Only batting counts, as
+/// the player never bowls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Performance {
+    pub won: bool,
+    pub runs: u32,
+    pub xp: u32,
+}
+
+impl Performance {
+    pub fn new(won: bool, runs: u32, timings: &[Timing]) -> Self {
+        let shots = timings
+            .iter()
+            .map(|timing| match timing {
+                Timing::Perfect => XP_PER_PERFECT,
+                Timing::Good => XP_PER_GOOD,
+                Timing::Early | Timing::Late => 0,
+            })
+            .sum::<u32>();
+        Self {
+            won,
+            runs,
+            xp: runs * XP_PER_RUN + shots + if won { XP_FOR_WIN } else { 0 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarMatch {
+    pub week: u32,
+    pub opponent: usize,
+    pub performance: Option<Performance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Season {
+    pub number: u32,
+    pub calendar: Vec<CalendarMatch>,
+}
+
+impl Season {
+    /// A match a week against opponents drawn from the other teams.
+    fn new(number: u32) -> Self {
+        let mut opponents = (0..TEAM_NAMES.len())
+            .filter(|&team| team != PLAYER)
+            .collect::<Vec<_>>();
+        opponents.shuffle(&mut rand::thread_rng());
+        Self {
+            number,
+            calendar: (1..)
+                .zip(opponents.into_iter().cycle().take(SEASON_LENGTH))
+                .map(|(week, opponent)| CalendarMatch {
+                    week,
+                    opponent,
+                    performance: None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn next_match(&self) -> Option<usize> {
+        self.calendar
+            .iter()
+            .position(|fixture| fixture.performance.is_none())
+    }
+}
+
+/// The player's career as a batter, saved between sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub xp: u32,
+    pub timing: u32,
+    pub power: u32,
+    pub accuracy: u32,
+    /// Indices into [`BATS`] and [`KITS`].
+    pub bat: usize,
+    pub kit: usize,
+    pub season: Season,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Self {
+            xp: 0,
+            timing: 0,
+            power: 0,
+            accuracy: 0,
+            bat: 0,
+            kit: 0,
+            season: Season::new(1),
+        }
+    }
+
+    /// The XP needed to reach `level`, growing with each level.
+    pub const fn level_xp(level: u32) -> u32 {
+        const XP_PER_LEVEL: u32 = 50;
+        XP_PER_LEVEL * level * (level - 1)
+    }
+
+    pub const fn level(&self) -> u32 {
+        let mut level = 1;
+        while self.xp >= Self::level_xp(level + 1) {
+            level += 1;
+        }
+        level
+    }
+
+    pub const fn attribute(&self, attribute: Attribute) -> u32 {
+        match attribute {
+            Attribute::Timing => self.timing,
+            Attribute::Power => self.power,
+            Attribute::Accuracy => self.accuracy,
+        }
+    }
+
+    /// One point to spend on attributes for every level after the first.
+    pub const fn unspent_points(&self) -> u32 {
+        (self.level() - 1).saturating_sub(self.timing + self.power + self.accuracy)
+    }
+
+    /// Puts a point into `attribute`, if there's one to spend and it isn't maxed out.
+    pub const fn upgrade(&mut self, attribute: Attribute) {
+        if self.unspent_points() == 0 || self.attribute(attribute) >= MAX_ATTRIBUTE {
+            return;
+        }
+        match attribute {
+            Attribute::Timing => self.timing += 1,
+            Attribute::Power => self.power += 1,
+            Attribute::Accuracy => self.accuracy += 1,
+        }
+    }
+
+    /// Switches to the next bat the player has unlocked.
+    pub fn cycle_bat(&mut self) {
+        self.bat = Self::next_unlocked(self.bat, BATS.map(|bat| bat.level), self.level());
+    }
+
+    pub fn cycle_kit(&mut self) {
+        self.kit = Self::next_unlocked(self.kit, KITS.map(|kit| kit.level), self.level());
+    }
+
+    fn next_unlocked<const N: usize>(current: usize, levels: [u32; N], level: u32) -> usize {
+        (1..=N)
+            .map(|offset| (current + offset) % N)
+            .find(|&index| levels[index] <= level)
+            .unwrap_or(current)
+    }
+
+    pub const fn bat(&self) -> Bat {
+        BATS[self.bat]
+    }
+
+    pub const fn kit(&self) -> Kit {
+        KITS[self.kit]
+    }
+
+    /// Scales the shot tuning by the player's attributes and bat.
+    pub fn apply(&self, tuning: &mut Tuning) {
+        let scale =
+            |attribute| (self.attribute(attribute) as f32).mul_add(attribute.per_point(), 1.);
+        let bat = self.bat();
+        let boost = |shot: &mut ShotTuning| {
+            shot.power *= scale(Attribute::Power) * bat.power;
+            shot.timing_window *= scale(Attribute::Timing) * bat.timing_window;
+        };
+        let shots = &mut tuning.shots;
+        for shot in [
+            &mut shots.block,
+            &mut shots.drive,
+            &mut shots.cut,
+            &mut shots.pull,
+            &mut shots.sweep,
+            &mut shots.loft,
+        ] {
+            boost(shot);
+        }
+        let edge_chance = &mut tuning.timing.edge_chance;
+        for chance in [
+            &mut edge_chance.early,
+            &mut edge_chance.good,
+            &mut edge_chance.perfect,
+            &mut edge_chance.late,
+        ] {
+            *chance *= scale(Attribute::Accuracy);
+        }
+    }
+
+    /// Records how the next calendar match went, starting a new season after the last one.
+    pub fn record(&mut self, performance: Performance) {
+        let Some(next) = self.season.next_match() else {
+            return;
+        };
+        self.season.calendar[next].performance = Some(performance);
+        self.xp += performance.xp;
+    }
+
+    pub fn start_next_season(&mut self) {
+        self.season = Season::new(self.season.number + 1);
+    }
+
+    /// The saved profile, if there is one.
+    pub fn load() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        match read_to_string(PATH) {
+            Ok(text) => Ok(Some(toml::from_str(&text)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(write(PATH, toml::to_string(self)?)?)
+    }
+}
+
+impl Game<'_> {
+    /// Awards the player XP for how they batted and saves, returning what they earned to add to
+    /// the summary.
+    pub fn record_career_match(profile: &mut Profile, teams: &Teams) -> String {
+        let (you, them) = if teams.batting.name == TEAM_NAMES[PLAYER] {
+            (&teams.batting, &teams.fielding)
+        } else {
+            (&teams.fielding, &teams.batting)
+        };
+        let level = profile.level();
+        let performance =
+            Performance::new(you.runs > them.runs, you.runs - you.extras, &you.timings);
+        profile.record(performance);
+        if let Err(error) = profile.save() {
+            eprintln!("couldn't save the career profile: {error}");
+        }
+        if profile.level() > level {
+            format!(", +{} XP, level {}!", performance.xp, profile.level())
+        } else {
+            format!(", +{} XP", performance.xp)
+        }
+    }
+
+    pub fn draw_career(&mut self) {
+        const HEADING_TOP: f32 = 4.;
+        const GAP: f32 = 3.;
+        const COLUMN_GAP: f32 = 8.;
+        const SMALL_TEXT_SIZE: u16 = 3;
+        let State::Career { resetting } = self.state else {
+            unreachable!()
+        };
+        let profile = self.career.get_or_insert_with(Profile::new);
+        let level = profile.level();
+        let progress = Node::column(
+            Self::LINE_GAP,
+            [
+                Node::text(format!("Level {level}"), Self::TEXT_SIZE),
+                Node::text(
+                    format!("{}/{} XP", profile.xp, Profile::level_xp(level + 1)),
+                    SMALL_TEXT_SIZE,
+                ),
+                Node::text(
+                    format!("Points: {}", profile.unspent_points()),
+                    SMALL_TEXT_SIZE,
+                ),
+            ],
+        );
+        let attributes = Attribute::VARIANTS
+            .iter()
+            .enumerate()
+            .map(|(id, &attribute)| {
+                let points = profile.attribute(attribute);
+                Node::text(
+                    format!("{attribute} {points}/{MAX_ATTRIBUTE}"),
+                    SMALL_TEXT_SIZE,
+                )
+                .padding(Self::BUTTON_PADDING)
+                .button(id)
+            });
+        let equipment = [
+            format!("Bat: {}", profile.bat().name),
+            format!("Kit: {}", profile.kit().name),
+        ]
+        .into_iter()
+        .zip(3..)
+        .map(|(text, id)| {
+            Node::text(text, SMALL_TEXT_SIZE)
+                .padding(Self::BUTTON_PADDING)
+                .button(id)
+        });
+        let next = profile.season.next_match();
+        let play = if next.is_some() {
+            "Play next match"
+        } else {
+            "Next season"
+        };
+        let actions = if resetting {
+            [("Start over", 6), ("Keep career", 7)]
+        } else {
+            [(play, 5), ("New career", 6)]
+        };
+        let status = if resetting {
+            "Start a new career? This one will be lost."
+        } else {
+            ""
+        };
+        let root = Node::column(
+            GAP,
+            [
+                Node::text("Career", Self::HEADING_TEXT_SIZE),
+                Node::row(
+                    COLUMN_GAP,
+                    [progress, Self::calendar(profile, SMALL_TEXT_SIZE)],
+                ),
+                Node::row(GAP, attributes.chain(equipment)),
+                Node::text(status, SMALL_TEXT_SIZE),
+                Node::row(
+                    GAP,
+                    actions.map(|(text, id)| {
+                        Node::text(text, Self::TEXT_SIZE)
+                            .padding(Self::BUTTON_PADDING)
+                            .button(id)
+                    }),
+                ),
+            ],
+        );
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Top, vec2(0., HEADING_TOP));
+        });
+        let Some(id) = activated else {
+            return;
+        };
+        // Starting over throws away the saved career, so it has to be pressed twice.
+        if id == 6 && !resetting {
+            self.state = State::Career { resetting: true };
+            return;
+        }
+        self.state = State::Career { resetting: false };
+        let profile = self.career.get_or_insert_with(Profile::new);
+        match id {
+            0..3 => profile.upgrade(Attribute::VARIANTS[id]),
+            3 => profile.cycle_bat(),
+            4 => profile.cycle_kit(),
+            5 if next.is_some() => {
+                self.competition = Some(Competition::Career);
+                self.state = State::PickingSide;
+            }
+            5 => profile.start_next_season(),
+            6 => *profile = Profile::new(),
+            _ => return,
+        }
+        if let Err(error) = profile.save() {
+            eprintln!("couldn't save the career profile: {error}");
+        }
+    }
+
+    /// This season's matches, with how the player did in those they've played.
+    fn calendar(profile: &Profile, text_size: u16) -> Node {
+        const GAP: f32 = 3.;
+        let column = |heading: &str, cell: &dyn Fn(&CalendarMatch) -> String| {
+            Node::column(
+                Self::LINE_GAP,
+                iter::once(Node::text(heading, text_size)).chain(
+                    profile
+                        .season
+                        .calendar
+                        .iter()
+                        .map(|fixture| Node::text(cell(fixture), text_size)),
+                ),
+            )
+        };
+        let result = |fixture: &CalendarMatch, cell: &dyn Fn(Performance) -> String| {
+            fixture.performance.map(cell).unwrap_or_default()
+        };
+        Node::column(
+            Self::LINE_GAP,
+            [
+                Node::text(format!("Season {}", profile.season.number), text_size),
+                Node::row(
+                    GAP,
+                    [
+                        column("Wk", &|fixture| fixture.week.to_string()),
+                        column("Opponent", &|fixture| {
+                            TEAM_NAMES[fixture.opponent].to_string()
+                        }),
+                        column("Result", &|fixture| {
+                            result(fixture, &|performance| {
+                                if performance.won { "Won" } else { "Lost" }.to_string()
+                            })
+                        }),
+                        column("Runs", &|fixture| {
+                            result(fixture, &|performance| performance.runs.to_string())
+                        }),
+                        column("XP", &|fixture| {
+                            result(fixture, &|performance| format!("+{}", performance.xp))
+                        }),
+                    ],
+                ),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(xp: u32) -> Profile {
+        Profile {
+            xp,
+            ..Profile::new()
+        }
+    }
+
+    #[test]
+    fn levels_need_more_xp_each_time() {
+        assert_eq!(
+            (1..=4).map(Profile::level_xp).collect::<Vec<_>>(),
+            [0, 100, 300, 600]
+        );
+        let levels = [(0, 1), (99, 1), (100, 2), (299, 2), (300, 3), (600, 4)];
+        for (xp, level) in levels {
+            assert_eq!(profile(xp).level(), level, "{xp} XP");
+        }
+    }
+
+    #[test]
+    fn upgrades_spend_a_point_a_level_up_to_the_cap() {
+        let mut profile = profile(Profile::level_xp(8));
+        assert_eq!(profile.unspent_points(), 7);
+        for _ in 0..MAX_ATTRIBUTE + 1 {
+            profile.upgrade(Attribute::Power);
+        }
+        assert_eq!(profile.power, MAX_ATTRIBUTE);
+        assert_eq!(profile.unspent_points(), 2);
+        for _ in 0..3 {
+            profile.upgrade(Attribute::Timing);
+        }
+        assert_eq!(profile.timing, 2);
+        assert_eq!(profile.unspent_points(), 0);
+        profile.upgrade(Attribute::Accuracy);
+        assert_eq!(profile.accuracy, 0);
+        assert_eq!(profile.attribute(Attribute::Timing), 2);
+    }
+
+    #[test]
+    fn a_new_profile_has_no_points_to_spend() {
+        let mut profile = Profile::new();
+        assert_eq!(profile.unspent_points(), 0);
+        profile.upgrade(Attribute::Timing);
+        assert_eq!(profile.timing, 0);
+    }
+
+    #[test]
+    fn cycling_skips_what_is_still_locked() {
+        let levels = [1, 3, 5, 8];
+        assert_eq!(Profile::next_unlocked(0, levels, 1), 0);
+        assert_eq!(Profile::next_unlocked(0, levels, 4), 1);
+        assert_eq!(Profile::next_unlocked(1, levels, 4), 0);
+        assert_eq!(Profile::next_unlocked(2, levels, 8), 3);
+        assert_eq!(Profile::next_unlocked(3, levels, 8), 0);
+    }
+
+    #[test]
+    fn attributes_and_bat_scale_the_shot_tuning() {
+        let base = Tuning::parse("").unwrap();
+        let profile = Profile {
+            timing: 2,
+            power: 1,
+            accuracy: 2,
+            bat: 1,
+            ..profile(Profile::level_xp(6))
+        };
+        let mut tuning = base;
+        profile.apply(&mut tuning);
+        let close = |actual: f32, expected: f32| (actual - expected).abs() < 1e-5;
+        let bat = BATS[1];
+        for (shot, base_shot) in [
+            (tuning.shots.drive, base.shots.drive),
+            (tuning.shots.loft, base.shots.loft),
+        ] {
+            assert!(close(shot.power, base_shot.power * 1.08 * bat.power));
+            assert!(close(
+                shot.timing_window,
+                base_shot.timing_window * 1.2 * bat.timing_window
+            ));
+            assert_eq!(shot.elevation, base_shot.elevation);
+        }
+        let edge_chance = tuning.timing.edge_chance;
+        assert!(close(
+            edge_chance.early,
+            base.timing.edge_chance.early * 0.7
+        ));
+        assert!(close(edge_chance.late, base.timing.edge_chance.late * 0.7));
+
+        let mut untouched = base;
+        Profile::new().apply(&mut untouched);
+        assert_eq!(untouched, base);
+    }
+
+    #[test]
+    fn performances_earn_xp_for_runs_timing_and_winning() {
+        let timings = [
+            Timing::Perfect,
+            Timing::Good,
+            Timing::Early,
+            Timing::Late,
+            Timing::Perfect,
+        ];
+        let won = Performance::new(true, 30, &timings);
+        assert_eq!(
+            won.xp,
+            30 * XP_PER_RUN + 2 * XP_PER_PERFECT + XP_PER_GOOD + XP_FOR_WIN
+        );
+        assert_eq!(
+            Performance::new(false, 30, &timings).xp,
+            won.xp - XP_FOR_WIN
+        );
+        assert_eq!(Performance::new(false, 0, &[]).xp, 0);
+    }
+
+    #[test]
+    fn profiles_record_matches_and_round_trip() {
+        let mut profile = Profile::new();
+        let performance = Performance::new(true, 12, &[Timing::Good]);
+        profile.record(performance);
+        profile.kit = 1;
+        assert_eq!(profile.xp, performance.xp);
+        assert_eq!(profile.season.calendar[0].performance, Some(performance));
+        assert_eq!(profile.season.next_match(), Some(1));
+        let text = toml::to_string(&profile).unwrap();
+        assert_eq!(toml::from_str::<Profile>(&text).unwrap(), profile);
+    }
+}
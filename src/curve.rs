@@ -0,0 +1,268 @@
+use std::ops::RangeInclusive;
+
+/// Implements calling a curve like a function, so `CURVE(x)` evaluates it. Only available with the
+/// `nightly` feature.
+#[cfg(feature = "nightly")]
+macro_rules! impl_fn {
+    ($curve:ident<$(const $generic:ident: usize),*>) => {
+        impl<$(const $generic: usize),*> FnOnce<(f32,)> for $curve<$($generic),*> {
+            type Output = f32;
+
+            extern "rust-call" fn call_once(self, args: (f32,)) -> Self::Output {
+                self.evaluate(args.0)
+            }
+        }
+
+        impl<$(const $generic: usize),*> FnMut<(f32,)> for $curve<$($generic),*> {
+            extern "rust-call" fn call_mut(&mut self, args: (f32,)) -> Self::Output {
+                self.evaluate(args.0)
+            }
+        }
+
+        impl<$(const $generic: usize),*> Fn<(f32,)> for $curve<$($generic),*> {
+            extern "rust-call" fn call(&self, args: (f32,)) -> Self::Output {
+                self.evaluate(args.0)
+            }
+        }
+    };
+}
+
+/// Coefficients from the highest power of x down to the constant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Polynomial<const N: usize>(pub [f32; N]);
+
+impl<const N: usize> Polynomial<N> {
+    /// Evaluates with Horner's method.
+    pub fn evaluate(self, x: f32) -> f32 {
+        self.0
+            .into_iter()
+            .fold(0., |acc, coefficient| acc.mul_add(x, coefficient))
+    }
+
+    /// Coefficients from the constant up, which is the order most of the maths wants.
+    fn ascending(self) -> impl Iterator<Item = f32> {
+        self.0.into_iter().rev()
+    }
+
+    /// Builds a polynomial from coefficients in ascending order, panicking if a non-zero one
+    /// doesn't fit.
+    fn from_ascending(coefficients: &[f32]) -> Self {
+        let mut result = [0.; N];
+        for (power, &coefficient) in coefficients.iter().enumerate() {
+            if power < N {
+                result[N - 1 - power] = coefficient;
+            } else {
+                assert!(
+                    coefficient == 0.,
+                    "a polynomial with an x^{power} term doesn't fit in {N} coefficients"
+                );
+            }
+        }
+        Self(result)
+    }
+
+    /// The highest power with a non-zero coefficient, or `None` for the zero polynomial.
+    pub fn degree(self) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|&coefficient| coefficient != 0.)
+            .map(|index| N - 1 - index)
+    }
+
+    pub fn derivative(self) -> Self {
+        let coefficients = self
+            .ascending()
+            .enumerate()
+            .skip(1)
+            .map(|(power, coefficient)| power as f32 * coefficient)
+            .collect::<Vec<_>>();
+        Self::from_ascending(&coefficients)
+    }
+
+    /// The real roots within `range`, in ascending order. Turning points split the range into
+    /// monotonic pieces, which are then bisected wherever the sign changes.
+    pub fn roots(self, range: RangeInclusive<f32>) -> Vec<f32> {
+        const ITERATIONS: usize = 64;
+        if self.degree().unwrap_or(0) == 0 {
+            return Vec::new();
+        }
+        let (start, end) = range.into_inner();
+        let mut points = vec![start];
+        points.extend(self.derivative().roots(start..=end));
+        points.push(end);
+        let mut roots = Vec::<f32>::new();
+        let mut push = |root: f32| {
+            if roots.last() != Some(&root) {
+                roots.push(root);
+            }
+        };
+        for pair in points.windows(2) {
+            let (mut low, mut high) = (pair[0], pair[1]);
+            let (low_value, high_value) = (self.evaluate(low), self.evaluate(high));
+            if low_value == 0. {
+                push(low);
+            }
+            if low_value * high_value >= 0. {
+                continue;
+            }
+            for _ in 0..ITERATIONS {
+                let middle = f32::midpoint(low, high);
+                if self.evaluate(middle) * low_value > 0. {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            push(f32::midpoint(low, high));
+        }
+        if self.evaluate(end) == 0. {
+            push(end);
+        }
+        roots
+    }
+
+    /// The least-squares fit of `samples`, as `(x, y)` pairs, or `None` if there are too few
+    /// distinct x values to pin down every coefficient.
+    pub fn fit(samples: &[(f32, f32)]) -> Option<Self> {
+        // Normal equations for the ascending coefficients, solved with Gaussian elimination.
+        let mut matrix = [[0_f64; N]; N];
+        let mut rhs = [0_f64; N];
+        for &(x, y) in samples {
+            let powers = (0..2 * N)
+                .scan(1., |power, _| {
+                    let current = *power;
+                    *power *= f64::from(x);
+                    Some(current)
+                })
+                .collect::<Vec<_>>();
+            for (row, (matrix_row, rhs)) in matrix.iter_mut().zip(&mut rhs).enumerate() {
+                *rhs = f64::from(y).mul_add(powers[row], *rhs);
+                for (column, element) in matrix_row.iter_mut().enumerate() {
+                    *element += powers[row + column];
+                }
+            }
+        }
+        for column in 0..N {
+            let pivot = (column..N)
+                .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+            if matrix[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            matrix.swap(column, pivot);
+            rhs.swap(column, pivot);
+            let (pivot_rows, rows) = matrix.split_at_mut(column + 1);
+            let pivot_row = &pivot_rows[column];
+            for (row, rhs_index) in rows.iter_mut().zip(column + 1..) {
+                let factor = row[column] / pivot_row[column];
+                for (element, pivot_element) in row.iter_mut().zip(pivot_row).skip(column) {
+                    *element = factor.mul_add(-pivot_element, *element);
+                }
+                rhs[rhs_index] = factor.mul_add(-rhs[column], rhs[rhs_index]);
+            }
+        }
+        let mut coefficients = [0_f64; N];
+        for row in (0..N).rev() {
+            let known = (row + 1..N)
+                .map(|column| matrix[row][column] * coefficients[column])
+                .sum::<f64>();
+            coefficients[row] = (rhs[row] - known) / matrix[row][row];
+        }
+        Some(Self::from_ascending(
+            &coefficients.map(|coefficient| coefficient as f32),
+        ))
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl_fn!(Polynomial<const N: usize>);
+
+/// Polynomials that each apply below their bound, the first matching one winning. The last bound
+/// is infinite. Splines from `poly!` are piecewise cubics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Piecewise<const P: usize, const N: usize> {
+    pub bounds: [f32; P],
+    pub pieces: [Polynomial<N>; P],
+}
+
+impl<const P: usize, const N: usize> Piecewise<P, N> {
+    pub fn evaluate(self, x: f32) -> f32 {
+        let index = self
+            .bounds
+            .iter()
+            .position(|&bound| x < bound)
+            .unwrap_or(P - 1);
+        self.pieces[index].evaluate(x)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl_fn!(Piecewise<const P: usize, const N: usize>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-3,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn derivative_drops_the_constant() {
+        let cubic = Polynomial([2., -3., 4., 5.]);
+        assert_eq!(cubic.derivative(), Polynomial([0., 6., -6., 4.]));
+        assert_eq!(Polynomial([7.]).derivative(), Polynomial([0.]));
+    }
+
+    #[test]
+    fn roots_are_ascending_within_the_range() {
+        // (x + 2)(x - 1)(x - 3)
+        let cubic = Polynomial([1., -2., -5., 6.]);
+        assert_close(&cubic.roots(-5.0..=5.), &[-2., 1., 3.]);
+        assert_close(&cubic.roots(0.0..=2.), &[1.]);
+        assert_close(&cubic.roots(-1.0..=1.), &[1.]);
+    }
+
+    #[test]
+    fn repeated_roots_are_found_once() {
+        // (x - 1)^2
+        assert_close(&Polynomial([1., -2., 1.]).roots(-5.0..=5.), &[1.]);
+        // (x - 2)^3
+        assert_close(&Polynomial([1., -6., 12., -8.]).roots(0.0..=5.), &[2.]);
+    }
+
+    #[test]
+    fn no_real_roots() {
+        assert!(Polynomial([1., 0., 1.]).roots(-10.0..=10.).is_empty());
+        assert!(Polynomial([3.]).roots(-10.0..=10.).is_empty());
+        assert!(Polynomial([0., 0.]).roots(-10.0..=10.).is_empty());
+    }
+
+    #[test]
+    fn fit_recovers_a_polynomial_from_its_samples() {
+        let cubic = Polynomial([-0.5, 2., -1., 1.]);
+        let samples = (0..=10)
+            .map(|step| step as f32 / 10.)
+            .map(|x| (x, cubic.evaluate(x)))
+            .collect::<Vec<_>>();
+        assert_close(&Polynomial::<4>::fit(&samples).unwrap().0, &cubic.0);
+    }
+
+    #[test]
+    fn fit_is_least_squares() {
+        // The best line through points either side of y = x is y = x.
+        let samples = [(0., 1.), (0., -1.), (2., 3.), (2., 1.)];
+        assert_close(&Polynomial::<2>::fit(&samples).unwrap().0, &[1., 0.]);
+    }
+
+    #[test]
+    fn fit_needs_enough_distinct_samples() {
+        assert_eq!(Polynomial::<3>::fit(&[(1., 1.), (1., 2.), (2., 3.)]), None);
+        assert_eq!(Polynomial::<2>::fit(&[]), None);
+    }
+}
@@ -0,0 +1,170 @@
+use macroquad::{
+    color::Color,
+    input::{get_char_pressed, is_key_pressed, KeyCode},
+    math::Vec3,
+    models::{draw_cube_wires, draw_line_3d, draw_sphere_wires},
+};
+use rapier3d::prelude::ColliderSet;
+use std::{
+    fmt::{self, Display, Formatter},
+    mem::take,
+    str::FromStr,
+};
+use strum::{EnumString, VariantNames};
+
+/// How many lines of output the console keeps.
+const LOG_LEN: usize = 6;
+
+/// Every command with its arguments, as shown by `help`.
+pub const USAGES: [&str; 4] = ["help", "deliver", "score <runs> [wickets]", "goto <screen>"];
+
+/// A screen the console can jump to, written in kebab case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, VariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Screen {
+    PickingSide,
+    TossingCoin,
+    FlippingCoin,
+    ShowingCoinResult,
+    Playing,
+    Tournament,
+    Career,
+    Nets,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    /// Starts a new delivery, keeping the teams if a match is being played.
+    Deliver,
+    /// Sets the batting team's score.
+    Score {
+        runs: u32,
+        wickets: u32,
+    },
+    Goto(Screen),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Unknown(String),
+    Usage(&'static str),
+    Screen(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "unknown command {name:?}, try help"),
+            Self::Usage(usage) => write!(f, "usage: {usage}"),
+            Self::Screen(screen) => write!(
+                f,
+                "unknown screen {screen:?}, expected one of {}",
+                Screen::VARIANTS.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Error> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let arguments = words.collect::<Vec<_>>();
+        let usage = || {
+            USAGES
+                .into_iter()
+                .find(|usage| usage.split(' ').next() == Some(name))
+                .map_or_else(|| Error::Unknown(name.to_string()), Error::Usage)
+        };
+        match (name, arguments.as_slice()) {
+            ("help", []) => Ok(Self::Help),
+            ("deliver", []) => Ok(Self::Deliver),
+            ("score", &[runs]) => Ok(Self::Score {
+                runs: runs.parse().map_err(|_| usage())?,
+                wickets: 0,
+            }),
+            ("score", &[runs, wickets]) => Ok(Self::Score {
+                runs: runs.parse().map_err(|_| usage())?,
+                wickets: wickets.parse().map_err(|_| usage())?,
+            }),
+            ("goto", &[screen]) => screen
+                .parse()
+                .map(Self::Goto)
+                .map_err(|_| Error::Screen(screen.to_string())),
+            _ => Err(usage()),
+        }
+    }
+}
+
+/// A line of typed input and the output of the commands run so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub log: Vec<String>,
+}
+
+impl Console {
+    /// Toggles the console with the backquote key and types into it while it's open, returning the
+    /// command entered this frame, if any. Invalid commands are reported in the log.
+    pub fn update(&mut self) -> Option<Command> {
+        let toggled = is_key_pressed(KeyCode::GraveAccent);
+        if toggled {
+            self.open = !self.open;
+        }
+        // Always drained, so keys pressed while closed don't show up when it opens.
+        while let Some(character) = get_char_pressed() {
+            if self.open && !toggled && !character.is_control() {
+                self.input.push(character);
+            }
+        }
+        if !self.open {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if !is_key_pressed(KeyCode::Enter) && !is_key_pressed(KeyCode::KpEnter) {
+            return None;
+        }
+        let line = take(&mut self.input);
+        self.print(format!("> {line}"));
+        if line.trim().is_empty() {
+            return None;
+        }
+        line.parse()
+            .map_err(|error: Error| self.print(error.to_string()))
+            .ok()
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > LOG_LEN {
+            self.log.remove(0);
+        }
+    }
+}
+
+/// Draws the outline of every ball and cuboid collider. Cuboids are drawn unrotated, which is all
+/// the ground needs.
+pub fn draw_colliders(colliders: &ColliderSet, colour: Color) {
+    for (_, collider) in colliders.iter() {
+        let position = Vec3::from(*collider.translation());
+        if let Some(ball) = collider.shape().as_ball() {
+            draw_sphere_wires(position, ball.radius, None, colour);
+        } else if let Some(cuboid) = collider.shape().as_cuboid() {
+            draw_cube_wires(position, Vec3::from(cuboid.half_extents) * 2., colour);
+        }
+    }
+}
+
+pub fn draw_path(points: &[Vec3], colour: Color) {
+    for pair in points.windows(2) {
+        draw_line_3d(pair[0], pair[1], colour);
+    }
+}
@@ -0,0 +1,194 @@
+use macroquad::{
+    color::{Color, WHITE},
+    material::{gl_use_default_material, gl_use_material, load_material, Material, MaterialParams},
+    math::{vec2, Vec2, Vec4},
+    miniquad::{ShaderSource, UniformDesc, UniformType},
+    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTEGER_SCALING: AtomicBool = AtomicBool::new(false);
+
+pub fn integer_scaling() -> bool {
+    INTEGER_SCALING.load(Ordering::Relaxed)
+}
+
+pub fn set_integer_scaling(integer_scaling: bool) {
+    INTEGER_SCALING.store(integer_scaling, Ordering::Relaxed);
+}
+
+/// How many screen pixels one virtual pixel takes up when fitting `size` into `screen_size`. With
+/// integer scaling on, this is rounded down to a whole number so every pixel is the same size,
+/// unless the screen is too small for even one screen pixel per virtual pixel.
+pub fn scale(screen_size: Vec2, size: Vec2) -> f32 {
+    let scale = (screen_size / size).min_element();
+    if integer_scaling() && scale >= 1. {
+        scale.floor()
+    } else {
+        scale
+    }
+}
+
+const VERTEX_SHADER: &str = "#version 100
+precision mediump float;
+
+attribute vec3 position;
+attribute vec2 texcoord;
+
+varying vec2 uv;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+}
+";
+
+/// Applies each enabled effect in turn: curvature bends the texture coordinates, dithering snaps
+/// each pixel to the palette with an ordered offset, and scanlines darken the bottom of each row.
+const FRAGMENT_SHADER: &str = "#version 100
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 SourceSize;
+uniform float Curvature;
+uniform float Dithering;
+uniform float Scanlines;
+uniform vec4 Palette[16];
+
+const float CURVATURE = 0.06;
+const float DITHER_SPREAD = 0.12;
+const float SCANLINE_DARKNESS = 0.3;
+const float PI = 3.14159265;
+
+float bayer2(vec2 position) {
+    position = floor(position);
+    return fract(dot(position, vec2(0.5, position.y * 0.75)));
+}
+
+float bayer4(vec2 position) {
+    return bayer2(0.5 * position) * 0.25 + bayer2(position);
+}
+
+vec3 nearest(vec3 colour) {
+    vec3 best = Palette[0].rgb;
+    float best_distance = distance(colour, best);
+    for (int i = 1; i < 16; i++) {
+        float candidate = distance(colour, Palette[i].rgb);
+        if (candidate < best_distance) {
+            best = Palette[i].rgb;
+            best_distance = candidate;
+        }
+    }
+    return best;
+}
+
+void main() {
+    vec2 position = uv;
+    if (Curvature > 0.5) {
+        vec2 centred = position * 2.0 - 1.0;
+        centred += centred * centred.yx * centred.yx * CURVATURE;
+        position = centred * 0.5 + 0.5;
+        if (position.x < 0.0 || position.x > 1.0 || position.y < 0.0 || position.y > 1.0) {
+            gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+            return;
+        }
+    }
+    vec4 colour = texture2D(Texture, position);
+    vec2 pixel = position * SourceSize;
+    if (Dithering > 0.5) {
+        float threshold = bayer4(pixel) - 0.5;
+        colour.rgb = nearest(colour.rgb + threshold * DITHER_SPREAD);
+    }
+    if (Scanlines > 0.5) {
+        float row = fract(pixel.y);
+        colour.rgb *= 1.0 - SCANLINE_DARKNESS * (1.0 - sin(row * PI));
+    }
+    gl_FragColor = colour;
+}
+";
+
+/// The effects applied when the render target is drawn to the screen.
+#[derive(Debug, Clone)]
+pub struct PostProcess {
+    material: Material,
+    pub curvature: bool,
+    pub dithering: bool,
+    pub scanlines: bool,
+}
+
+impl PostProcess {
+    /// Compiles the shader, with dithering limited to the colours of `palette`.
+    pub fn new(palette: &[Color]) -> Self {
+        let material = load_material(
+            ShaderSource::Glsl {
+                vertex: VERTEX_SHADER,
+                fragment: FRAGMENT_SHADER,
+            },
+            MaterialParams {
+                uniforms: vec![
+                    UniformDesc::new("SourceSize", UniformType::Float2),
+                    UniformDesc::new("Curvature", UniformType::Float1),
+                    UniformDesc::new("Dithering", UniformType::Float1),
+                    UniformDesc::new("Scanlines", UniformType::Float1),
+                    UniformDesc::new("Palette", UniformType::Float4).array(16),
+                ],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let post_process = Self {
+            material,
+            curvature: false,
+            dithering: false,
+            scanlines: false,
+        };
+        post_process.set_palette(palette);
+        post_process
+    }
+
+    /// Changes the colours dithering snaps to, using the first 16 of `palette`.
+    pub fn set_palette(&self, palette: &[Color]) {
+        let mut colours = [Vec4::ZERO; 16];
+        for (colour, palette) in colours.iter_mut().zip(palette) {
+            *colour = palette.to_vec();
+        }
+        self.material.set_uniform_array("Palette", &colours);
+    }
+
+    const fn enabled(&self) -> bool {
+        self.curvature || self.dithering || self.scanlines
+    }
+
+    /// Draws a render target's texture, which is stored upside down, with the enabled effects.
+    pub fn draw(&self, texture: &Texture2D, position: Vec2, size: Vec2) {
+        let enabled = self.enabled();
+        if enabled {
+            let flag = |enabled| if enabled { 1_f32 } else { 0. };
+            self.material
+                .set_uniform("SourceSize", vec2(texture.width(), texture.height()));
+            self.material.set_uniform("Curvature", flag(self.curvature));
+            self.material.set_uniform("Dithering", flag(self.dithering));
+            self.material.set_uniform("Scanlines", flag(self.scanlines));
+            gl_use_material(&self.material);
+        }
+        draw_texture_ex(
+            texture,
+            position.x,
+            position.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(size),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        if enabled {
+            gl_use_default_material();
+        }
+    }
+}
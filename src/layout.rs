@@ -0,0 +1,234 @@
+use crate::text::TextBlock;
+use macroquad::{
+    math::{vec2, Rect, Vec2},
+    texture::Texture2D,
+};
+
+/// Which point of the screen, and of the laid out node, line up with each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Top,
+    Centre,
+}
+
+impl Anchor {
+    const fn fraction(self) -> Vec2 {
+        match self {
+            Self::Top => vec2(0.5, 0.),
+            Self::Centre => vec2(0.5, 0.5),
+        }
+    }
+}
+
+/// A piece of UI measured in virtual units. Rows line their children up along the top, and columns
+/// centre theirs horizontally.
+#[derive(Debug, Clone)]
+pub enum Node {
+    Texture {
+        texture: Texture2D,
+        size: Vec2,
+    },
+    Text {
+        text: String,
+        size: u16,
+        max_width: Option<f32>,
+    },
+    Space(Vec2),
+    Row {
+        gap: f32,
+        children: Vec<Self>,
+    },
+    Column {
+        gap: f32,
+        children: Vec<Self>,
+    },
+    Padding {
+        padding: f32,
+        child: Box<Self>,
+    },
+    /// A clickable, focusable area around its child, with an id that is reported when activated.
+    Button {
+        id: usize,
+        child: Box<Self>,
+    },
+}
+
+impl Node {
+    pub const fn texture(texture: Texture2D, size: Vec2) -> Self {
+        Self::Texture { texture, size }
+    }
+
+    pub fn text(text: impl Into<String>, size: u16) -> Self {
+        Self::Text {
+            text: text.into(),
+            size,
+            max_width: None,
+        }
+    }
+
+    pub fn wrapped(text: impl Into<String>, size: u16, max_width: f32) -> Self {
+        Self::Text {
+            text: text.into(),
+            size,
+            max_width: Some(max_width),
+        }
+    }
+
+    pub const fn space(size: Vec2) -> Self {
+        Self::Space(size)
+    }
+
+    pub fn row(gap: f32, children: impl IntoIterator<Item = Self>) -> Self {
+        Self::Row {
+            gap,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    pub fn column(gap: f32, children: impl IntoIterator<Item = Self>) -> Self {
+        Self::Column {
+            gap,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    pub fn padding(self, padding: f32) -> Self {
+        Self::Padding {
+            padding,
+            child: Box::new(self),
+        }
+    }
+
+    pub fn button(self, id: usize) -> Self {
+        Self::Button {
+            id,
+            child: Box::new(self),
+        }
+    }
+
+    fn measure(&self, text_block: &mut impl FnMut(&str, u16, Option<f32>) -> TextBlock) -> Vec2 {
+        match self {
+            Self::Texture { size, .. } | Self::Space(size) => *size,
+            Self::Text {
+                text,
+                size,
+                max_width,
+            } => text_block(text, *size, *max_width).size,
+            Self::Row { gap, children } => {
+                let sizes = children.iter().map(|child| child.measure(text_block));
+                let (width, height) = sizes.fold((0_f32, 0_f32), |(width, height), size| {
+                    (width + size.x, height.max(size.y))
+                });
+                vec2(width + Self::gaps(*gap, children.len()), height)
+            }
+            Self::Column { gap, children } => {
+                let sizes = children.iter().map(|child| child.measure(text_block));
+                let (width, height) = sizes.fold((0_f32, 0_f32), |(width, height), size| {
+                    (width.max(size.x), height + size.y)
+                });
+                vec2(width, height + Self::gaps(*gap, children.len()))
+            }
+            Self::Padding { padding, child } => {
+                child.measure(text_block) + Vec2::splat(2. * padding)
+            }
+            Self::Button { child, .. } => child.measure(text_block),
+        }
+    }
+
+    fn gaps(gap: f32, len: usize) -> f32 {
+        gap * len.saturating_sub(1) as f32
+    }
+
+    fn place(
+        &self,
+        position: Vec2,
+        text_block: &mut impl FnMut(&str, u16, Option<f32>) -> TextBlock,
+        placed: &mut Vec<Placed>,
+    ) {
+        match self {
+            Self::Texture { texture, size } => placed.push(Placed::Texture {
+                texture: texture.clone(),
+                rect: Rect::new(position.x, position.y, size.x, size.y),
+            }),
+            Self::Text {
+                text,
+                size,
+                max_width,
+            } => {
+                let block = text_block(text, *size, *max_width);
+                placed.push(Placed::Text {
+                    anchor: position + vec2(block.size.x / 2., 0.),
+                    block,
+                    size: *size,
+                });
+            }
+            Self::Space(_) => {}
+            Self::Row { gap, children } => {
+                let mut x = position.x;
+                for child in children {
+                    child.place(vec2(x, position.y), text_block, placed);
+                    x += child.measure(text_block).x + gap;
+                }
+            }
+            Self::Column { gap, children } => {
+                let width = self.measure(text_block).x;
+                let mut y = position.y;
+                for child in children {
+                    let size = child.measure(text_block);
+                    child.place(
+                        vec2(position.x + (width - size.x) / 2., y),
+                        text_block,
+                        placed,
+                    );
+                    y += size.y + gap;
+                }
+            }
+            Self::Padding { padding, child } => {
+                child.place(position + Vec2::splat(*padding), text_block, placed);
+            }
+            Self::Button { id, child } => {
+                let size = child.measure(text_block);
+                placed.push(Placed::Button {
+                    id: *id,
+                    rect: Rect::new(position.x, position.y, size.x, size.y),
+                });
+                child.place(position, text_block, placed);
+            }
+        }
+    }
+
+    /// Positions the node within `area` so that the `anchor` points of both line up, then moves it
+    /// by `offset`. `text_block` lays out centred text in virtual units.
+    pub fn layout(
+        &self,
+        area: Vec2,
+        anchor: Anchor,
+        offset: Vec2,
+        mut text_block: impl FnMut(&str, u16, Option<f32>) -> TextBlock,
+    ) -> Vec<Placed> {
+        let size = self.measure(&mut text_block);
+        let position = anchor.fraction() * (area - size) + offset;
+        let mut placed = Vec::new();
+        self.place(position, &mut text_block, &mut placed);
+        placed
+    }
+}
+
+/// A drawable piece of a laid out [`Node`], in virtual units. Buttons come before their contents.
+#[derive(Debug, Clone)]
+pub enum Placed {
+    Button {
+        id: usize,
+        rect: Rect,
+    },
+    Texture {
+        texture: Texture2D,
+        rect: Rect,
+    },
+    Text {
+        block: TextBlock,
+        size: u16,
+        /// The top centre of the text block.
+        anchor: Vec2,
+    },
+}
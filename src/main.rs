@@ -1,4 +1,4 @@
-#![feature(fn_traits, unboxed_closures)]
+#![cfg_attr(feature = "nightly", feature(fn_traits, unboxed_closures))]
 #![allow(
     clippy::cast_possible_truncation,
     clippy::cast_lossless,
@@ -6,26 +6,50 @@
     clippy::cast_precision_loss
 )]
 
+mod button;
+mod career;
+mod curve;
+mod debug;
+mod display;
+mod layout;
+mod nets;
+mod palette;
+mod review;
+mod shot;
+mod text;
+mod tournament;
+mod trajectory;
+mod tuning;
+mod umpire;
+
+use button::{ButtonState, Buttons, Gamepads, NavigationInput};
+use career::Profile;
+use curve::{Piecewise, Polynomial};
+use debug::{Command, Console, Screen};
+use display::PostProcess;
+use layout::{Anchor, Node, Placed};
 use macroquad::camera::{set_camera, set_default_camera, Camera3D, Projection};
 use macroquad::color::{Color, BLACK, WHITE};
 use macroquad::input::{
-    is_mouse_button_pressed, mouse_delta_position, set_cursor_grab, show_mouse,
+    is_key_pressed, is_mouse_button_pressed, mouse_delta_position, set_cursor_grab, show_mouse,
+    KeyCode,
 };
 use macroquad::math::{vec3, Quat, Rect, Vec3};
 use macroquad::miniquad::conf::Platform;
 use macroquad::miniquad::window::set_mouse_cursor;
 use macroquad::miniquad::CursorIcon;
 use macroquad::models::{
-    draw_affine_parallelogram, draw_cylinder, draw_line_3d, draw_plane, draw_sphere,
+    draw_affine_parallelogram, draw_cube, draw_cylinder, draw_line_3d, draw_plane, draw_sphere,
+    draw_sphere_wires,
 };
-use macroquad::texture::{draw_texture_ex, render_target, DrawTextureParams, Image, RenderTarget};
-use macroquad::time::{get_frame_time, get_time};
+use macroquad::texture::{render_target, Image, RenderTarget};
+use macroquad::time::{get_fps, get_frame_time, get_time};
 use macroquad::ui::{Style, Ui};
 use macroquad::window::Conf;
 use macroquad::{
-    input::{is_mouse_button_released, mouse_position_local, MouseButton},
+    input::{is_mouse_button_released, mouse_position, mouse_position_local, MouseButton},
     prelude::FilterMode,
-    text::{load_ttf_font_from_bytes, measure_text, Font, TextDimensions},
+    text::{draw_text_ex, load_ttf_font_from_bytes, Font, TextParams},
     ui::{
         hash, root_ui,
         widgets::{Texture, Window},
@@ -33,6 +57,7 @@ use macroquad::{
     },
 };
 use nalgebra::Vector3;
+use nets::{Drill, Nets, Records};
 use rand::distributions::{Distribution, Standard};
 use rand::{random, Rng};
 use rapier3d::na::vector;
@@ -41,16 +66,22 @@ use rapier3d::prelude::{
     IntegrationParameters, IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline,
     QueryPipeline, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
 };
-use retro_wicket_macros::{hex, include_textures, poly, poly_consts};
+use retro_wicket_macros::{hex, include_animation, palette, poly_consts};
+use review::{Appeal, Frame, Review, Stumps, Tracking};
+use shot::{Contact, Shot, ShotInput, Timing};
 use std::f32::consts::PI;
 use std::time::Instant;
 use std::{
-    cmp::Ordering,
     collections::HashMap,
-    mem::swap,
+    iter,
+    mem::{discriminant, swap},
     ops::{Deref, DerefMut},
 };
-use strum::Display;
+use strum::{Display, IntoStaticStr};
+use text::{TextBlock, TextLayout};
+use tournament::{Innings, Tournament, PLAYER, TEAM_NAMES};
+use tuning::Tuning;
+use umpire::{Call, Delivery, Over};
 
 use macroquad::{
     main,
@@ -96,9 +127,63 @@ async fn main() {
 
 struct Game<'n> {
     state: State<'n>,
+    buttons: Buttons,
+    gamepads: Gamepads,
+    assets: Assets,
     font: Font,
-    text_measurer: TextMeasurer,
+    text: TextLayout,
     render_target: RenderTarget,
+    post_process: PostProcess,
+    settings_open: bool,
+    debug_overlay: bool,
+    console: Console,
+    batting_aid: bool,
+    tournament: Option<Tournament>,
+    career: Option<Profile>,
+    /// What the match being played counts towards, if anything.
+    competition: Option<Competition>,
+    nets: Nets,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Competition {
+    /// A tournament fixture, by index.
+    Tournament(usize),
+    /// The next match on the career calendar.
+    Career,
+}
+
+/// Every embedded image, decoded once at startup. Textures are reference counted, so cloning one
+/// out of here is cheap.
+#[derive(Debug, Clone)]
+struct Assets {
+    heads: Texture2D,
+    tails: Texture2D,
+    bat: Texture2D,
+    field: Texture2D,
+    coin_drag: Texture2D,
+    coin_flip: Animation,
+    batter: Animation,
+    bowler: Animation,
+    umpire: Animation,
+    fielder: Animation,
+}
+
+impl Assets {
+    fn load() -> Self {
+        Self {
+            heads: include_texture!("heads"),
+            tails: include_texture!("tails"),
+            bat: include_texture!("bat"),
+            field: include_texture!("field"),
+            coin_drag: include_texture!("coin-drag"),
+            coin_flip: include_animation!("coin-flip"),
+            batter: include_animation!("batter"),
+            bowler: include_animation!("bowler"),
+            umpire: include_animation!("umpire"),
+            fielder: include_animation!("fielder"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
@@ -118,15 +203,16 @@ impl Distribution<CoinSide> for Standard {
 }
 
 impl CoinSide {
-    fn texture(self) -> Texture2D {
+    fn texture(self, assets: &Assets) -> Texture2D {
         match self {
-            Self::Heads => include_texture!("heads"),
-            Self::Tails => include_texture!("tails"),
+            Self::Heads => assets.heads.clone(),
+            Self::Tails => assets.tails.clone(),
         }
     }
 }
 
 #[allow(clippy::large_enum_variant)]
+#[derive(IntoStaticStr)]
 enum State<'n> {
     PickingSide,
     TossingCoin {
@@ -143,21 +229,81 @@ enum State<'n> {
         opponent_choice: Role,
     },
     Playing {
-        innings: usize,
         teams: Teams<'n>,
 
         start: f32,
 
         batting_direction: f32,
         ball_thrown: bool,
+        shot: Option<Shot>,
+        contact: Option<Contact>,
         ball_hit: bool,
+        /// The ball's predicted path once it's released, for the batting aid and the debug
+        /// overlay, worked out again whenever the ball is sent somewhere new.
+        predicted_path: Option<Vec<Vec3>>,
 
         camera_target: Vec3,
         camera_position: Vec3,
 
+        batter: AnimatedSprite,
+        bowler: Fielder,
+        /// Added to the bowler's hand position at release, so no two deliveries are the same.
+        release_variation: Vec3,
+        /// How far past the usual spot the bowler's front foot lands.
+        stride: f32,
+        umpire: AnimatedSprite,
+        /// The umpire's call, decided when the ball is released, and when they signal it.
+        call: Option<Call>,
+        called_at: Option<f32>,
+        /// Whether the batter can only be out run out, after a no-ball.
+        free_hit: bool,
+        fielders: Vec<Fielder>,
+
+        /// How far the batter has run towards the other end.
+        run: f32,
+        /// Whether a fielder has thrown the ball back to the bowler's end.
+        thrown_in: bool,
+        /// Everything that's happened since the ball was released, for reviewing.
+        frames: Vec<Frame>,
+        tracking: Option<Tracking>,
+        appeal: Option<Appeal>,
+        /// When the ball stopped being in play.
+        dead_at: Option<f32>,
+
         physics_stuff: PhysicsStuff,
         ball_rigidbody_handle: RigidBodyHandle,
     },
+    ShowingMatchResult {
+        summary: String,
+        /// Where to go back to afterwards.
+        competition: Option<Competition>,
+    },
+    /// The points tables and next fixture, or the number of teams to start a tournament with if
+    /// there isn't one.
+    Tournament {
+        teams: usize,
+    },
+    /// The player's level, attributes and equipment, and the season calendar.
+    Career {
+        /// Whether "New career" has been pressed and is waiting to be confirmed.
+        resetting: bool,
+    },
+    /// Picking a delivery to practise against or a drill, with how the last drill went.
+    Nets {
+        result: Option<(Drill, u32)>,
+    },
+    /// The third umpire going over a replay of the last delivery.
+    Reviewing {
+        teams: Teams<'n>,
+        review: Review,
+        /// The replay frame on screen.
+        frame: usize,
+        /// Whether the replay is being stepped through a frame at a time rather than played.
+        stepping: bool,
+        decided_at: Option<f32>,
+        batter: AnimatedSprite,
+        fielder: AnimatedSprite,
+    },
 }
 
 struct PhysicsStuff {
@@ -194,12 +340,42 @@ impl PhysicsStuff {
             &(),
         );
     }
+
+    /// Where `body` is now and will be over the next `duration` seconds, found by stepping a copy of
+    /// the world.
+    fn predict(&self, body: RigidBodyHandle, duration: f32) -> Vec<Vec3> {
+        const STEP: f32 = 1. / 60.;
+        let mut prediction = Self {
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            gravity: self.gravity,
+            integration_parameters: self.integration_parameters,
+            physics_pipeline: PhysicsPipeline::new(),
+            islands: self.islands.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            impulse_joints: self.impulse_joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            query_pipeline: self.query_pipeline.clone(),
+        };
+        let position = (*self.bodies[body].translation()).into();
+        iter::once(position)
+            .chain((0..(duration / STEP) as usize).map(|_| {
+                prediction.step(STEP);
+                (*prediction.bodies[body].translation()).into()
+            }))
+            .collect()
+    }
 }
 
 impl State<'_> {
-    fn start() -> Self {
+    fn start(assets: &Assets) -> Self {
         // TODO remove this
-        Game::init_playing_state(Teams::new(["You", "Opponent"]))
+        Game::init_playing_state(
+            Teams::new(["You", "Opponent"], tuning::current().overs),
+            assets,
+        )
     }
 }
 
@@ -207,17 +383,55 @@ impl State<'_> {
 struct Teams<'n> {
     batting: Team<'n>,
     fielding: Team<'n>,
+    over: Over,
+    innings: usize,
+    overs: u32,
 }
 
 impl<'n> Teams<'n> {
+    /// Starts the second innings.
     fn switch(&mut self) {
         swap(&mut self.batting, &mut self.fielding);
+        self.batting.reviews = review::REVIEWS_PER_INNINGS;
+        self.fielding.reviews = review::REVIEWS_PER_INNINGS;
+        self.over = Over::default();
+        self.innings += 1;
+    }
+
+    /// What the side batting second needs to win.
+    fn target(&self) -> Option<u32> {
+        (self.innings == 1).then_some(self.fielding.runs + 1)
     }
 
-    const fn new(names: [&'n str; 2]) -> Self {
+    /// Whether the batting side is bowled out, has faced all its overs, or has reached its target.
+    fn innings_over(&self) -> bool {
+        self.batting.wickets >= tournament::WICKETS
+            || self.batting.balls >= self.overs * Over::BALLS
+            || self
+                .target()
+                .is_some_and(|target| self.batting.runs >= target)
+    }
+
+    /// The side an umpire's decision went against, who can review it.
+    const fn reviewing(&mut self, appeal: Appeal) -> &mut Team<'n> {
+        if appeal.given {
+            &mut self.batting
+        } else {
+            &mut self.fielding
+        }
+    }
+
+    const fn new(names: [&'n str; 2], overs: u32) -> Self {
         Self {
-            batting: Team { name: names[0] },
-            fielding: Team { name: names[1] },
+            batting: Team::new(names[0]),
+            fielding: Team::new(names[1]),
+            over: Over {
+                legal_balls: 0,
+                bouncers: 0,
+                free_hit: false,
+            },
+            innings: 0,
+            overs,
         }
     }
 }
@@ -225,43 +439,40 @@ impl<'n> Teams<'n> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Team<'n> {
     name: &'n str,
+    runs: u32,
+    wickets: u32,
+    /// Runs from no-balls and wides, which are also counted in `runs`.
+    extras: u32,
+    /// Legal deliveries faced.
+    balls: u32,
+    /// How many more unsuccessful reviews they can make this innings.
+    reviews: u32,
+    /// How each shot that made contact was timed, in order.
+    timings: Vec<Timing>,
 }
 
-#[derive(Debug, Clone)]
-struct TextMeasurer {
-    font_data: &'static [u8],
-    cache: HashMap<TextMeasureInput, TextDimensions>,
-}
-
-impl TextMeasurer {
-    fn new(font_data: &'static [u8]) -> Self {
+impl<'n> Team<'n> {
+    const fn new(name: &'n str) -> Self {
         Self {
-            cache: HashMap::new(),
-            font_data,
+            name,
+            runs: 0,
+            wickets: 0,
+            extras: 0,
+            balls: 0,
+            reviews: review::REVIEWS_PER_INNINGS,
+            timings: Vec::new(),
         }
     }
 
-    fn measure(&mut self, input: TextMeasureInput) -> TextDimensions {
-        if let Some(dimensions) = self.cache.get(&input) {
-            return *dimensions;
+    const fn innings(&self) -> Innings {
+        Innings {
+            runs: self.runs,
+            wickets: self.wickets,
+            balls: self.balls,
         }
-        let dimensions = measure_text(
-            &input.text,
-            Some(&load_ttf_font_from_bytes(self.font_data).unwrap()),
-            input.size,
-            1.,
-        );
-        self.cache.insert(input, dimensions);
-        dimensions
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct TextMeasureInput {
-    text: String,
-    size: u16,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Role {
     Batting,
@@ -279,74 +490,45 @@ impl Distribution<Role> for Standard {
 }
 
 impl Role {
-    fn texture(self) -> Texture2D {
+    fn texture(self, assets: &Assets) -> Texture2D {
         match self {
-            Self::Batting => include_texture!("bat"),
-            Self::Fielding => include_texture!("field"),
+            Self::Batting => assets.bat.clone(),
+            Self::Fielding => assets.field.clone(),
         }
     }
 }
 
-enum ScreenSide {
-    Left,
-    Right,
-}
-
-impl ScreenSide {
-    fn from_mouse_position() -> Self {
-        match mouse_position_local().x.total_cmp(&0.) {
-            Ordering::Less | Ordering::Equal => Self::Left,
-            Ordering::Greater => Self::Right,
-        }
+palette! {
+    /// A slot in the active palette, named after its colour in the default one.
+    enum Colour in "palettes/endesga-16.hex" {
+        Birch,
+        Oak,
+        Pine,
+        Darkbark,
+        Blood,
+        Fabric,
+        Candle,
+        Glow,
+        Flora,
+        Moss,
+        Mold,
+        Iron,
+        Aluminiu,
+        White,
+        Ion,
+        Archaeon,
     }
 }
 
-#[allow(unused)]
-enum Colour {
-    Birch,
-    Oak,
-    Pine,
-    Darkbark,
-    Blood,
-    Fabric,
-    Candle,
-    Glow,
-    Flora,
-    Moss,
-    Mold,
-    Iron,
-    Aluminiu,
-    White,
-    Ion,
-    Archaeon,
-}
-
 impl Colour {
-    const fn colour(self) -> Color {
-        match self {
-            Self::Birch => hex!(e4a672),
-            Self::Oak => hex!(b86f50),
-            Self::Pine => hex!(743f39),
-            Self::Darkbark => hex!(3f2832),
-            Self::Blood => hex!(9e2835),
-            Self::Fabric => hex!(e53b44),
-            Self::Candle => hex!(fb922b),
-            Self::Glow => hex!(ffe762),
-            Self::Flora => hex!(63c64d),
-            Self::Moss => hex!(327345),
-            Self::Mold => hex!(193d3f),
-            Self::Iron => hex!(4f6781),
-            Self::Aluminiu => hex!(afbfd2),
-            Self::White => hex!(ffffff),
-            Self::Ion => hex!(2ce8f4),
-            Self::Archaeon => hex!(0484d1),
-        }
+    fn themed(self) -> Color {
+        palette::active().colours[self as usize]
     }
 }
 
 macro_rules! colour {
     ($colour:ident) => {
-        Colour::$colour.colour()
+        Colour::$colour.themed()
     };
 }
 
@@ -363,75 +545,283 @@ fn box_muller() -> (f32, f32) {
     (r * f32::cos(theta), r * f32::sin(theta))
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Polynomial<const N: usize>([f32; N]);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Playback {
+    Once,
+    Repeat(u16),
+    Loop,
+}
 
-impl<const N: usize> Polynomial<N> {
-    fn evaluate(self, x: f32) -> f32 {
-        self.0
-            .into_iter()
-            .rev()
-            .enumerate()
-            .fold(0., |acc, (index, coefficient)| {
-                coefficient.mul_add(x.powf(index as f32), acc)
-            })
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnimationDirection {
+    Forward,
+    PingPong,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AnimationTag {
+    name: &'static str,
+    from: usize,
+    to: usize,
+    direction: AnimationDirection,
+    playback: Playback,
+}
+
+impl AnimationTag {
+    fn sequence(self) -> Vec<usize> {
+        let forward = self.from..=self.to;
+        let back = (self.from + 1..self.to).rev();
+        match self.direction {
+            AnimationDirection::Forward => forward.collect(),
+            AnimationDirection::PingPong => forward.chain(back).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Animation {
+    frames: Vec<Texture2D>,
+    durations: Vec<f32>,
+    tags: Vec<AnimationTag>,
+}
+
+impl Animation {
+    fn new(frames: Vec<Texture2D>, durations: Vec<f32>, tags: Vec<AnimationTag>) -> Self {
+        assert_eq!(frames.len(), durations.len());
+        Self {
+            frames,
+            durations,
+            tags,
+        }
+    }
+
+    fn tag(&self, name: &str) -> Option<AnimationTag> {
+        self.tags.iter().find(|tag| tag.name == name).copied()
+    }
+
+    fn duration(&self) -> f32 {
+        self.durations.iter().sum()
+    }
+
+    fn is_finished(&self, time: f32, playback: Playback) -> bool {
+        time >= Self::plays(playback) * self.duration()
+    }
+
+    fn frame(&self, time: f32, playback: Playback) -> &Texture2D {
+        let sequence = (0..self.frames.len()).collect::<Vec<_>>();
+        &self.frames[self.sequence_frame(&sequence, time, playback)]
+    }
+
+    fn tag_frame(&self, name: &str, time: f32) -> Option<&Texture2D> {
+        let tag = self.tag(name)?;
+        Some(&self.frames[self.sequence_frame(&tag.sequence(), time, tag.playback)])
+    }
+
+    fn is_tag_finished(&self, name: &str, time: f32) -> Option<bool> {
+        let tag = self.tag(name)?;
+        Some(time >= Self::plays(tag.playback) * self.sequence_duration(&tag.sequence()))
+    }
+
+    fn sequence_duration(&self, sequence: &[usize]) -> f32 {
+        sequence.iter().map(|&index| self.durations[index]).sum()
+    }
+
+    const fn plays(playback: Playback) -> f32 {
+        match playback {
+            Playback::Once => 1.,
+            Playback::Repeat(times) => times as f32,
+            Playback::Loop => f32::INFINITY,
+        }
+    }
+
+    fn sequence_frame(&self, sequence: &[usize], time: f32, playback: Playback) -> usize {
+        let last = *sequence.last().unwrap();
+        let duration = self.sequence_duration(sequence);
+        if time >= Self::plays(playback) * duration {
+            return last;
+        }
+        let mut time = time.max(0.) % duration;
+        for &index in sequence {
+            if time < self.durations[index] {
+                return index;
+            }
+            time -= self.durations[index];
+        }
+        last
     }
 }
 
-impl<const N: usize> FnOnce<(f32,)> for Polynomial<N> {
-    type Output = f32;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+enum SpriteState {
+    Stance,
+    RunUp,
+    Delivery,
+    Backswing,
+    Block,
+    Drive,
+    Cut,
+    Pull,
+    Sweep,
+    Loft,
+    Leave,
+    Running,
+    Celebrating,
+    NoBall,
+    Wide,
+}
 
-    extern "rust-call" fn call_once(self, args: (f32,)) -> Self::Output {
-        self.evaluate(args.0)
+impl SpriteState {
+    /// How to play the whole sprite when it has no tag named after this state.
+    const fn fallback_playback(self) -> Playback {
+        match self {
+            Self::Stance
+            | Self::RunUp
+            | Self::Running
+            | Self::Celebrating
+            | Self::NoBall
+            | Self::Wide => Playback::Loop,
+            Self::Delivery
+            | Self::Backswing
+            | Self::Block
+            | Self::Drive
+            | Self::Cut
+            | Self::Pull
+            | Self::Sweep
+            | Self::Loft
+            | Self::Leave => Playback::Once,
+        }
     }
 }
 
-impl<const N: usize> FnMut<(f32,)> for Polynomial<N> {
-    extern "rust-call" fn call_mut(&mut self, args: (f32,)) -> Self::Output {
-        self.evaluate(args.0)
+impl From<Shot> for SpriteState {
+    fn from(shot: Shot) -> Self {
+        match shot {
+            Shot::Block => Self::Block,
+            Shot::Drive => Self::Drive,
+            Shot::Cut => Self::Cut,
+            Shot::Pull => Self::Pull,
+            Shot::Sweep => Self::Sweep,
+            Shot::Loft => Self::Loft,
+            Shot::Leave => Self::Leave,
+        }
     }
 }
 
-impl<const N: usize> Fn<(f32,)> for Polynomial<N> {
-    extern "rust-call" fn call(&self, args: (f32,)) -> Self::Output {
-        self.evaluate(args.0)
+#[derive(Debug, Clone)]
+struct AnimatedSprite {
+    animation: Animation,
+    state: SpriteState,
+    state_start: f32,
+    size: f32,
+}
+
+impl AnimatedSprite {
+    const fn new(animation: Animation, size: f32, time: f32) -> Self {
+        Self {
+            animation,
+            state: SpriteState::Stance,
+            state_start: time,
+            size,
+        }
+    }
+
+    fn set_state(&mut self, state: SpriteState, time: f32) {
+        if self.state != state {
+            self.state = state;
+            self.state_start = time;
+        }
+    }
+
+    fn is_finished(&self, time: f32) -> bool {
+        let time = time - self.state_start;
+        self.animation
+            .is_tag_finished(&self.state.to_string(), time)
+            .unwrap_or_else(|| {
+                self.animation
+                    .is_finished(time, self.state.fallback_playback())
+            })
+    }
+
+    fn texture(&self, time: f32) -> &Texture2D {
+        let time = time - self.state_start;
+        self.animation
+            .tag_frame(&self.state.to_string(), time)
+            .unwrap_or_else(|| self.animation.frame(time, self.state.fallback_playback()))
+    }
+
+    /// Draws the sprite standing at `position`, turned about the vertical axis to face the camera.
+    fn draw(&self, position: Vec3, camera_position: Vec3, time: f32) {
+        self.draw_tinted(position, camera_position, time, WHITE);
+    }
+
+    fn draw_tinted(&self, position: Vec3, camera_position: Vec3, time: f32, tint: Color) {
+        let to_camera = (camera_position - position) * vec3(1., 0., 1.);
+        let right = Vec3::Y.cross(to_camera).try_normalize().unwrap_or(Vec3::X) * self.size;
+        draw_affine_parallelogram(
+            position - right / 2. + Vec3::Y * self.size,
+            Vec3::NEG_Y * self.size,
+            right,
+            Some(self.texture(time)),
+            tint,
+        );
     }
 }
 
+#[derive(Debug, Clone)]
+struct Fielder {
+    sprite: AnimatedSprite,
+    position: Vec3,
+}
+
 impl<'n> Game<'n> {
     fn new() -> Self {
         let font_data = include_bytes!("fonts/Quinque Five Font.ttf");
         let font = load_ttf_font_from_bytes(font_data).unwrap();
         let render_target = render_target(Self::SIZE.x as u32, Self::SIZE.y as u32);
         render_target.texture.set_filter(FilterMode::Nearest);
+        let assets = Assets::load();
         Self {
-            state: State::start(),
+            state: State::start(&assets),
+            buttons: Buttons::default(),
+            gamepads: Gamepads::new(),
+            assets,
+            text: TextLayout::new(font.clone()),
             font,
-            text_measurer: TextMeasurer::new(font_data),
             render_target,
+            post_process: PostProcess::new(&palette::active().colours),
+            settings_open: false,
+            debug_overlay: false,
+            console: Console::default(),
+            batting_aid: false,
+            tournament: Tournament::load().unwrap_or_else(|error| {
+                eprintln!("couldn't load the saved tournament: {error}");
+                None
+            }),
+            career: Profile::load().unwrap_or_else(|error| {
+                eprintln!("couldn't load the career profile: {error}");
+                None
+            }),
+            competition: None,
+            nets: Nets {
+                records: Records::load().unwrap_or_else(|error| {
+                    eprintln!("couldn't load the nets records: {error}");
+                    Records::default()
+                }),
+                ..Nets::default()
+            },
         }
     }
 
     fn draw_borders() {
-        let size_pixels = Self::transform_size(Self::SIZE);
+        let screen_size = Self::screen_size();
         let position = Self::transform_point(Vec2::ZERO);
+        let end = Self::transform_point(Self::SIZE);
 
-        draw_rectangle(0., 0., position.x, size_pixels.y, BLACK);
-        draw_rectangle(
-            position.x + size_pixels.x,
-            0.,
-            position.x,
-            size_pixels.y,
-            BLACK,
-        );
-        draw_rectangle(0., 0., size_pixels.x, position.y, BLACK);
-        draw_rectangle(
-            0.,
-            position.y + size_pixels.y,
-            size_pixels.x,
-            position.y,
-            BLACK,
-        );
+        draw_rectangle(0., 0., position.x, screen_size.y, BLACK);
+        draw_rectangle(end.x, 0., screen_size.x - end.x, screen_size.y, BLACK);
+        draw_rectangle(0., 0., screen_size.x, position.y, BLACK);
+        draw_rectangle(0., end.y, screen_size.x, screen_size.y - end.y, BLACK);
     }
 
     fn screen_size() -> Vec2 {
@@ -439,12 +829,16 @@ impl<'n> Game<'n> {
     }
 
     fn scale() -> f32 {
-        let screen_size = Self::screen_size();
-        (screen_size / Self::SIZE).min_element()
+        display::scale(Self::screen_size(), Self::SIZE)
+    }
+
+    /// The top left of the virtual screen, on a whole pixel so integer scaling stays crisp.
+    fn origin() -> Vec2 {
+        ((Self::screen_size() - Self::transform_size(Self::SIZE)) / 2.).floor()
     }
 
     fn transform_point(point: Vec2) -> Vec2 {
-        Self::scale() * point + Self::screen_size() / 2. - Self::scale() * Self::SIZE / 2.
+        Self::scale() * point + Self::origin()
     }
 
     fn transform_size(size: Vec2) -> Vec2 {
@@ -455,15 +849,58 @@ impl<'n> Game<'n> {
         Self::scale() * length
     }
 
-    fn untransform_length(length: f32) -> f32 {
-        length / Self::scale()
+    fn untransform_point(point: Vec2) -> Vec2 {
+        (point - Self::origin()) / Self::scale()
+    }
+
+    fn untransform_size(size: Vec2) -> Vec2 {
+        size / Self::scale()
+    }
+
+    /// Lays out text at a virtual font size, with the returned block measured in virtual units.
+    fn text_block(
+        &mut self,
+        text: &str,
+        size: u16,
+        max_width: Option<f32>,
+        line_gap: f32,
+    ) -> TextBlock {
+        let mut block = self.text.layout(
+            text,
+            Self::transform_length(size as f32) as u16,
+            max_width.map(Self::transform_length),
+            Self::transform_length(line_gap),
+        );
+        for line in &mut block.lines {
+            line.position = Self::untransform_size(line.position);
+        }
+        block.size = Self::untransform_size(block.size);
+        block
+    }
+
+    /// Draws a block from [`Self::text_block`] with the skin for its size already pushed.
+    fn draw_text_block(ui: &mut Ui, block: &TextBlock, anchor: Vec2) {
+        for line in &block.lines {
+            ui.label(Self::transform_size(anchor + line.position), &line.text);
+        }
     }
 
     async fn run(&mut self) {
         loop {
+            tuning::reload_if_changed();
             set_default_camera();
             Self::draw_borders();
+            self.gamepads.update();
+            let screen = discriminant(&self.state);
+            if is_key_pressed(KeyCode::Escape) || self.gamepads.pressed(gilrs::Button::Start) {
+                self.settings_open = !self.settings_open;
+                self.buttons = Buttons::default();
+            }
             set_mouse_cursor(match &mut self.state {
+                _ if self.settings_open => {
+                    self.draw_settings();
+                    CursorIcon::Pointer
+                }
                 State::PickingSide => {
                     self.draw_picking_side();
                     CursorIcon::Pointer
@@ -484,7 +921,31 @@ impl<'n> Game<'n> {
                     self.draw_playing();
                     CursorIcon::Crosshair
                 }
+                State::Reviewing { .. } => {
+                    self.draw_reviewing();
+                    CursorIcon::Default
+                }
+                State::ShowingMatchResult { .. } => {
+                    self.draw_showing_match_result();
+                    CursorIcon::Pointer
+                }
+                State::Tournament { .. } => {
+                    self.draw_tournament();
+                    CursorIcon::Pointer
+                }
+                State::Career { .. } => {
+                    self.draw_career();
+                    CursorIcon::Pointer
+                }
+                State::Nets { .. } => {
+                    self.draw_nets();
+                    CursorIcon::Pointer
+                }
             });
+            self.draw_debug();
+            if discriminant(&self.state) != screen {
+                self.buttons = Buttons::default();
+            }
             next_frame().await;
         }
     }
@@ -493,24 +954,266 @@ impl<'n> Game<'n> {
         self.draw_playing_to_render_texture();
 
         set_default_camera();
-        let position = Self::transform_point(Vec2::ZERO);
-        draw_texture_ex(
+        self.post_process.draw(
             &self.render_target.texture,
+            Self::transform_point(Vec2::ZERO),
+            Self::transform_size(Self::SIZE),
+        );
+        self.draw_scoreboard();
+        self.draw_countdown();
+        self.draw_contact_feedback();
+        self.draw_call();
+        self.draw_appeal();
+        self.finish_delivery();
+    }
+
+    /// Once the ball is dead, gives whoever an umpire's decision went against a moment to review
+    /// it, then settles it and starts the next delivery.
+    fn finish_delivery(&mut self) {
+        const NEXT_DELIVERY_DELAY: f32 = 3.;
+        let State::Playing {
+            teams,
+            dead_at: Some(dead_at),
+            appeal,
+            frames,
+            ..
+        } = &mut self.state
+        else {
+            return;
+        };
+        let time = get_time() as f32;
+        let review_open = appeal.is_some_and(|appeal| {
+            appeal.reviewable() && teams.reviewing(appeal).reviews > 0 && !self.console.open
+        });
+        if let Some(appeal) = appeal.filter(|_| review_open && is_key_pressed(KeyCode::R)) {
+            self.start_review(appeal);
+            return;
+        }
+        let delay = if review_open {
+            Self::REVIEW_WINDOW
+        } else {
+            NEXT_DELIVERY_DELAY
+        };
+        if time - *dead_at < delay {
+            return;
+        }
+        if appeal.is_some_and(|appeal| appeal.given) {
+            teams.batting.wickets += 1;
+        }
+        let carry = Self::carry(frames);
+        let teams = teams.clone();
+        self.next_delivery(teams, carry);
+    }
+
+    /// Carries on with the next delivery, starting the second innings or finishing the match if
+    /// this innings is over. In the nets, scores the ball instead, going back to the nets once a
+    /// drill is done.
+    fn next_delivery(&mut self, mut teams: Teams<'n>, carry: Vec3) {
+        if self.nets.session.is_some() {
+            if self.score_nets_ball(&teams, carry) {
+                return;
+            }
+        } else if teams.innings_over() {
+            if teams.innings > 0 {
+                self.finish_match(&teams);
+                return;
+            }
+            teams.switch();
+        }
+        self.state = Self::init_playing_state(teams, &self.assets);
+    }
+
+    /// The furthest the ball got from the batter along the ground during a delivery.
+    fn carry(frames: &[Frame]) -> Vec3 {
+        frames
+            .iter()
+            .map(|frame| (frame.ball - Self::BATTER_POSITION) * vec3(1., 0., 1.))
+            .max_by(|a, b| a.length().total_cmp(&b.length()))
+            .unwrap_or_default()
+    }
+
+    /// Shows how the match went, recording and saving the result if it was a tournament fixture or
+    /// a career match.
+    fn finish_match(&mut self, teams: &Teams) {
+        let mut summary = tournament::summary(
+            (teams.fielding.name, teams.fielding.innings()),
+            (teams.batting.name, teams.batting.innings()),
+        );
+        let competition = self.competition.take();
+        match (competition, &mut self.tournament, &mut self.career) {
+            (Some(Competition::Tournament(fixture)), Some(tournament), _) => {
+                Self::record_fixture(tournament, fixture, teams);
+            }
+            (Some(Competition::Career), _, Some(profile)) => {
+                summary += &Self::record_career_match(profile, teams);
+            }
+            _ => {}
+        }
+        self.state = State::ShowingMatchResult {
+            summary,
+            competition,
+        };
+    }
+
+    /// The batting side's score and overs, and what they need if they're chasing. In a drill, the
+    /// score so far and what it's for.
+    fn draw_scoreboard(&mut self) {
+        const TOP: f32 = 8.;
+        const GOAL_GAP: f32 = 6.;
+        let State::Playing { ref teams, .. } = self.state else {
+            return;
+        };
+        let batting = &teams.batting;
+        if let Some(session) = self.nets.session {
+            let text = session.drill.map_or_else(
+                || format!("Nets {}/{}", batting.runs, batting.wickets),
+                |drill| {
+                    format!(
+                        "{drill} {} - ball {}/{}",
+                        session.score,
+                        session.balls + 1,
+                        drill.balls()
+                    )
+                },
+            );
+            self.draw_caption(&text, TOP, Self::CAPTION_COLOUR.themed());
+            if let Some(drill) = session.drill {
+                self.draw_caption(drill.goal(), TOP + GOAL_GAP, Self::CAPTION_COLOUR.themed());
+            }
+            return;
+        }
+        let mut text = format!(
+            "{} {}/{} ({}.{}/{})",
+            batting.name,
+            batting.runs,
+            batting.wickets,
+            batting.balls / Over::BALLS,
+            batting.balls % Over::BALLS,
+            teams.overs
+        );
+        if let Some(target) = teams.target() {
+            text += &format!(" need {}", target.saturating_sub(batting.runs));
+        }
+        self.draw_caption(&text, TOP, Self::CAPTION_COLOUR.themed());
+    }
+
+    const CAPTION_COLOUR: Colour = Colour::White;
+
+    /// Counts down the seconds until the bowler starts their run-up, then says to get ready until
+    /// the ball is released.
+    fn draw_countdown(&mut self) {
+        let State::Playing {
+            start,
+            ball_thrown: false,
+            ref teams,
+            ..
+        } = self.state
+        else {
+            return;
+        };
+        let free_hit = teams.over.free_hit;
+        let tuning = tuning::current();
+        let until_run_up = tuning.ball_delay - tuning.run_up_time - (get_time() as f32 - start);
+        let text = if until_run_up > 0. {
+            (until_run_up.ceil() as u32).to_string()
+        } else {
+            "Ready".to_string()
+        };
+        self.draw_banner(&text, Self::BANNER_TOP, Self::COUNTDOWN_COLOUR.themed());
+        if free_hit {
+            self.draw_banner(
+                "Free hit",
+                Self::BANNER_TOP + Self::BANNER_GAP,
+                Self::FREE_HIT_COLOUR.themed(),
+            );
+        }
+    }
+
+    const COUNTDOWN_COLOUR: Colour = Colour::Candle;
+    const FREE_HIT_COLOUR: Colour = Colour::Glow;
+    const BANNER_TOP: f32 = 20.;
+    const BANNER_GAP: f32 = 14.;
+
+    /// Shows the umpire's call for a moment after they make it.
+    fn draw_call(&mut self) {
+        const CALL_TIME: f32 = 2.;
+        let State::Playing {
+            call: Some(call),
+            called_at: Some(called_at),
+            ..
+        } = self.state
+        else {
+            return;
+        };
+        if get_time() as f32 - called_at > CALL_TIME {
+            return;
+        }
+        self.draw_banner(
+            &call.to_string(),
+            Self::BANNER_TOP + Self::BANNER_GAP,
+            Self::CALL_COLOUR.themed(),
+        );
+    }
+
+    const CALL_COLOUR: Colour = Colour::Blood;
+
+    /// Shows how the last shot was timed for a moment after it was played.
+    fn draw_contact_feedback(&mut self) {
+        const FEEDBACK_TIME: f32 = 1.5;
+        let State::Playing {
+            contact: Some(contact),
+            ..
+        } = self.state
+        else {
+            return;
+        };
+        if get_time() as f32 - contact.time > FEEDBACK_TIME {
+            return;
+        }
+        let text = if contact.edged {
+            format!("{} - edged!", contact.timing)
+        } else {
+            format!("{}!", contact.timing)
+        };
+        let colour = match contact.timing {
+            Timing::Perfect => Self::PERFECT_COLOUR,
+            Timing::Good => Self::GOOD_COLOUR,
+            Timing::Early | Timing::Late => Self::MISTIMED_COLOUR,
+        };
+        self.draw_banner(&text, Self::BANNER_TOP, colour.themed());
+    }
+
+    /// Draws a heading across the top of the playing view.
+    fn draw_banner(&mut self, text: &str, top: f32, colour: Color) {
+        self.draw_centred_text(text, Self::HEADING_TEXT_SIZE, top, colour);
+    }
+
+    fn draw_caption(&mut self, text: &str, top: f32, colour: Color) {
+        self.draw_centred_text(text, Self::TEXT_SIZE, top, colour);
+    }
+
+    fn draw_centred_text(&mut self, text: &str, size: u16, top: f32, colour: Color) {
+        let font_size = Self::transform_length(size as f32) as u16;
+        let width = self.text.measure(text, font_size).width;
+        let position = Self::transform_point(vec2(Self::SIZE.x / 2., top)) - vec2(width / 2., 0.);
+        draw_text_ex(
+            text,
             position.x,
             position.y,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(Self::transform_size(Self::SIZE)),
-                flip_y: true,
+            TextParams {
+                font: Some(&self.font),
+                font_size,
+                color: colour,
                 ..Default::default()
             },
         );
     }
 
-    const BALL_RADIUS: f32 = 0.036;
+    const PERFECT_COLOUR: Colour = Colour::Glow;
+    const GOOD_COLOUR: Colour = Colour::Flora;
+    const MISTIMED_COLOUR: Colour = Colour::Blood;
 
-    const TARGET: Vec3 = vec3(0., 0., 0.);
-    const POSITION: Vec3 = vec3(0., 5., 18.);
+    const BALL_RADIUS: f32 = 0.036;
 
     const BOWLING_CREASE_TO_END: f32 = 1.22;
     const PITCH_WIDTH: f32 = 3.05;
@@ -525,116 +1228,433 @@ impl<'n> Game<'n> {
     const STUMP_DISTANCE: f32 = Self::STUMP_DIAMETER + Self::BETWEEN_STUMPS;
     const PITCH_LENGTH: f32 = Self::BETWEEN_WICKETS + 2. * Self::BOWLING_CREASE_TO_END;
 
-    const LINE_COLOUR: Color = colour!(White);
-    const GRASS_COLOUR: Color = colour!(Flora);
-    const PITCH_COLOUR: Color = colour!(Birch);
-    const BALL_COLOUR: Color = colour!(Fabric);
-
-    const BALL_DELAY: f32 = 3.;
-    fn draw_playing_to_render_texture(&mut self) {
-        let State::Playing {
-            innings,
-            teams,
-
-            start,
-
-            batting_direction,
-            ball_thrown,
-            ball_hit,
+    const LINE_COLOUR: Colour = Colour::White;
+    const GRASS_COLOUR: Colour = Colour::Flora;
+    const PITCH_COLOUR: Colour = Colour::Birch;
+    const BALL_COLOUR: Colour = Colour::Fabric;
+
+    const BATTER_POSITION: Vec3 = vec3(
+        0.,
+        0.,
+        Self::BETWEEN_WICKETS / 2. - Self::BOWLING_CREASE_TO_POPPING_CREASE / 2.,
+    );
+    /// The popping crease the batter stands behind, which the ball is bowled towards.
+    const BATTING_CREASE: f32 = Self::BETWEEN_WICKETS / 2. - Self::BOWLING_CREASE_TO_POPPING_CREASE;
+    const BATTER_SIZE: f32 = 2.;
+    const FIELDER_POSITIONS: [Vec3; 9] = [
+        vec3(0., 0., 13.),
+        vec3(-2., 0., 13.),
+        vec3(-20., 0., 8.),
+        vec3(22., 0., 10.),
+        vec3(-14., 0., -4.),
+        vec3(14., 0., -4.),
+        vec3(-35., 0., -30.),
+        vec3(35., 0., -30.),
+        vec3(0., 0., -55.),
+    ];
+    const BOUNDARY_DISTANCE: f32 = 65.;
+    /// Where the bowler's front foot lands when they let go of the ball.
+    const RELEASE_POSITION: Vec3 = vec3(
+        0.,
+        0.,
+        -Self::BETWEEN_WICKETS / 2. + Self::BOWLING_CREASE_TO_POPPING_CREASE / 2.,
+    );
+
+    /// The popping crease at the bowler's end, which their front foot has to land behind.
+    const BOWLING_POPPING_CREASE: f32 =
+        -Self::BETWEEN_WICKETS / 2. + Self::BOWLING_CREASE_TO_POPPING_CREASE;
+    const UMPIRE_POSITION: Vec3 = vec3(-1., 0., -Self::BETWEEN_WICKETS / 2. - 1.5);
+    const BATTING_STUMPS: Stumps = Stumps {
+        z: Self::BETWEEN_WICKETS / 2.,
+        half_width: Self::STUMP_DISTANCE + Self::STUMP_DIAMETER / 2. + Self::BALL_RADIUS,
+        height: Self::STUMP_HEIGHT + Self::BALL_RADIUS,
+    };
+    /// How far the batter has to run to complete a run.
+    const RUN_LENGTH: f32 = Self::BATTER_POSITION.z - Self::BOWLING_POPPING_CREASE;
 
-            camera_position,
-            camera_target,
+    poly_consts! {
+        const RUN_UP => spline [(0, 0), (0.4, 0.25), (1, 1)];
+    }
 
-            physics_stuff,
-            ball_rigidbody_handle: ball_body_handle,
-        } = &mut self.state
-        else {
-            unreachable!()
-        };
+    /// Where the bowler's front foot is `elapsed` seconds into a delivery. They wait at the end of
+    /// their run-up, run in so they reach the crease as the ball is released, speeding up along
+    /// [`Self::RUN_UP`], then stay there.
+    fn bowler_position(elapsed: f32, stride: f32, tuning: &Tuning) -> Vec3 {
+        let run_up_start = tuning.ball_delay - tuning.run_up_time;
+        let time = ((elapsed - run_up_start) / tuning.run_up_time).clamp(0., 1.);
+        let progress = Self::RUN_UP.evaluate(time);
+        Self::RELEASE_POSITION
+            + Vec3::Z * stride
+            + Vec3::NEG_Z * tuning.run_up_length * (1. - progress)
+    }
 
+    /// Points the camera and draws the ground, pitch markings and stumps.
+    fn draw_field(render_target: &RenderTarget, position: Vec3, target: Vec3) {
         set_camera(&Camera3D {
             aspect: Some(Self::SIZE.x / Self::SIZE.y),
-            target: *camera_target,
-            position: *camera_position,
+            target,
+            position,
             up: Vec3::Y,
             fovy: 30_f32.to_radians(),
             projection: Projection::Perspective,
             viewport: None,
-            render_target: Some(self.render_target.clone()),
+            render_target: Some(render_target.clone()),
         });
         clear_background(colour!(Ion));
-        draw_plane(Vec3::ZERO, vec2(1000., 1000.), None, Self::GRASS_COLOUR);
+        draw_plane(
+            Vec3::ZERO,
+            vec2(1000., 1000.),
+            None,
+            Self::GRASS_COLOUR.themed(),
+        );
         draw_plane(
             Vec3::ZERO,
             vec2(Self::PITCH_WIDTH / 2., Self::PITCH_LENGTH / 2.),
             None,
-            Self::PITCH_COLOUR,
+            Self::PITCH_COLOUR.themed(),
         );
-        let batter_size = 2.;
-        let textures = [include_texture!("batter")];
         Self::draw_sides();
         Self::draw_stumps();
-        draw_affine_parallelogram(
-            vec3(
-                -batter_size / 2.,
-                batter_size,
-                Game::BETWEEN_WICKETS / 2. - Game::BOWLING_CREASE_TO_POPPING_CREASE / 2.,
-            ),
-            Vec3::NEG_Y * batter_size,
-            Vec3::X * batter_size,
-            Some(&textures[((get_time() * 2.) as usize) % textures.len()]),
-            WHITE,
-        );
+    }
+
+    fn draw_playing_to_render_texture(&mut self) {
+        let time = get_time() as f32;
+        let frame_time = get_frame_time();
+        let (tuning, kit, bat) = self.batting_tuning();
+        self.draw_players(time, kit, &tuning);
+        self.release_ball(time, frame_time, &tuning);
+        self.play_shot(time, frame_time, bat, &tuning);
+        self.call_delivery(time, &tuning);
+        self.adjudicate(time, frame_time, &tuning);
+
+        let State::Playing {
+            ball_thrown,
+            ball_hit,
+            predicted_path,
+            physics_stuff,
+            ball_rigidbody_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let path = (*ball_thrown && (self.batting_aid || self.debug_overlay)).then(|| {
+            predicted_path
+                .get_or_insert_with(|| {
+                    physics_stuff.predict(*ball_rigidbody_handle, Self::PREDICTION_TIME)
+                })
+                .as_slice()
+        });
+        if let Some(path) = path.filter(|_| self.batting_aid) {
+            Self::draw_batting_aid(path, *ball_hit);
+        }
+        if self.debug_overlay {
+            Self::draw_physics_debug(physics_stuff, *ball_rigidbody_handle, path);
+        }
+    }
+
+    /// The tuning the batter plays with, and the colours of their kit and bat. In a career match
+    /// the player bats with their own attributes, bat and kit.
+    fn batting_tuning(&self) -> (Tuning, Color, Colour) {
+        let State::Playing { teams, .. } = &self.state else {
+            unreachable!()
+        };
+        let mut tuning = tuning::current();
+        let profile = self.career.as_ref().filter(|_| {
+            self.competition == Some(Competition::Career)
+                && teams.batting.name == TEAM_NAMES[PLAYER]
+        });
+        if let Some(profile) = profile {
+            profile.apply(&mut tuning);
+        }
+        let kit = profile.map_or(WHITE, |profile| profile.kit().colour.themed());
+        let bat = profile.map_or(Colour::Fabric, |profile| profile.bat().colour);
+        (tuning, kit, bat)
+    }
 
-        let get_frame_time = get_frame_time();
-        physics_stuff.step(get_frame_time);
+    /// Draws the field and everyone on it, moving the bowler through their run-up.
+    fn draw_players(&mut self, time: f32, kit: Color, tuning: &Tuning) {
+        let State::Playing {
+            start,
+            ball_thrown,
+            camera_position,
+            camera_target,
+            batter,
+            bowler,
+            stride,
+            umpire,
+            fielders,
+            run,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        Self::draw_field(&self.render_target, *camera_position, *camera_target);
+        let batter_position = Self::BATTER_POSITION + Vec3::NEG_Z * *run;
+        batter.draw_tinted(batter_position, *camera_position, time, kit);
+        let elapsed = time - *start;
+        bowler.position = Self::bowler_position(elapsed, *stride, tuning);
+        if *ball_thrown {
+            if bowler.sprite.state == SpriteState::Delivery && bowler.sprite.is_finished(time) {
+                bowler.sprite.set_state(SpriteState::Stance, time);
+            }
+        } else if elapsed > tuning.ball_delay - tuning.run_up_time {
+            bowler.sprite.set_state(SpriteState::RunUp, time);
+        }
+        bowler.sprite.draw(bowler.position, *camera_position, time);
+        umpire.draw(Self::UMPIRE_POSITION, *camera_position, time);
+        for fielder in fielders.iter() {
+            fielder
+                .sprite
+                .draw(fielder.position, *camera_position, time);
+        }
+    }
+
+    /// Steps the physics, carrying the ball in the bowler's hand until it's time to release it,
+    /// then bowls it and has the umpire judge the delivery.
+    fn release_ball(&mut self, time: f32, frame_time: f32, tuning: &Tuning) {
+        let State::Playing {
+            teams,
+            start,
+            ball_thrown,
+            batter,
+            bowler,
+            release_variation,
+            call,
+            physics_stuff,
+            ball_rigidbody_handle: ball_body_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        physics_stuff.step(frame_time);
+        if *ball_thrown {
+            return;
+        }
+        let setup = self.nets.session.map(|session| session.setup);
+        let hand = bowler.position + tuning.release_offset + *release_variation;
         let ball_body = &mut physics_stuff.bodies[*ball_body_handle];
-        Self::draw_ball((*ball_body.translation()).into());
-        if get_time() as f32 - *start > Self::BALL_DELAY && !*ball_thrown {
-            ball_body.set_enabled(true);
-            ball_body.add_force(vector![0., 0., 2.], true);
-            *ball_thrown = true;
+        ball_body.set_translation(hand.into(), true);
+        if time - *start <= tuning.ball_delay {
+            return;
         }
-        let delta = mouse_delta_position() * Self::DELTA_MULTIPLIER;
+        ball_body.set_enabled(true);
+        if let Some(setup) = setup {
+            let gravity = -physics_stuff.gravity.y;
+            let (velocity, swing) = setup.release(hand, Self::BATTING_CREASE, gravity, tuning);
+            ball_body.set_linvel(Vector3::from(velocity), true);
+            ball_body.add_force(Vector3::from(swing), true);
+        } else {
+            ball_body.add_force(vector![0., 0., tuning.ball_force], true);
+        }
+        *ball_thrown = true;
+        batter.set_state(SpriteState::Backswing, time);
+        bowler.sprite.set_state(SpriteState::Delivery, time);
+        let path = physics_stuff.predict(*ball_body_handle, Self::JUDGING_TIME);
+        let (pitch, arrival) = trajectory::delivery(&path, Self::BATTING_CREASE);
+        let delivery = Delivery {
+            overstep: bowler.position.z - Self::BOWLING_POPPING_CREASE,
+            pitch,
+            arrival,
+        };
+        *call = teams.over.judge(delivery, tuning);
+    }
+
+    /// Turns the bat with the mouse, and plays a shot when the mouse is swung, sending the ball
+    /// off the bat if it's in reach.
+    fn play_shot(&mut self, time: f32, frame_time: f32, bat: Colour, tuning: &Tuning) {
+        let State::Playing {
+            teams,
+            batting_direction,
+            ball_thrown,
+            shot,
+            contact,
+            ball_hit,
+            predicted_path,
+            batter,
+            physics_stuff,
+            ball_rigidbody_handle: ball_body_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let ball_body = &mut physics_stuff.bodies[*ball_body_handle];
+        Self::draw_ball((*ball_body.translation()).into());
+        let delta = mouse_delta_position() * tuning.delta_multiplier;
         *batting_direction += delta.x;
         *batting_direction = batting_direction.clamp(
-            -Self::BATTING_DIRECTION_LIMIT,
-            Self::BATTING_DIRECTION_LIMIT,
-        );
-        let start = vec3(
-            0.,
-            0.,
-            Game::BETWEEN_WICKETS / 2. - Game::BOWLING_CREASE_TO_POPPING_CREASE / 2.,
+            -tuning.batting_direction_limit,
+            tuning.batting_direction_limit,
         );
+        let start = Self::BATTER_POSITION;
         draw_line_3d(
             start,
             start + Quat::from_axis_angle(Vec3::Y, *batting_direction) * Vec3::NEG_Z,
-            colour!(Fabric),
+            bat.themed(),
         );
         show_mouse(false);
         set_cursor_grab(true);
-        let speed = delta.y / get_frame_time;
-        if speed > Self::BATTING_SPEED_THRESHOLD && !*ball_hit {
-            let direction = Quat::from_axis_angle(Vec3::Y, *batting_direction) * Vec3::NEG_Z;
-            let velocity = direction * speed;
-            let delta_velocity = velocity - Vec3::from(*ball_body.linvel());
-            let distance_error = f32::abs(start.z - ball_body.translation().z);
-            let delta_velocity = delta_velocity
-                * Self::DISTANCE_ERROR_TO_VELOCITY_MULTIPLIER(distance_error).clamp(0., 1.);
-            ball_body.set_linvel(
-                dbg!(ball_body.linvel() + Vector3::from(delta_velocity)),
-                true,
-            );
-            *ball_hit = true;
+        let speed = delta.y / frame_time;
+        let selected = (!self.console.open)
+            .then(|| ShotInput::read(speed, *batting_direction))
+            .and_then(|input| Shot::select(input, tuning));
+        let Some(selected) = selected.filter(|_| *ball_thrown && shot.is_none()) else {
+            return;
+        };
+        *shot = Some(selected);
+        batter.set_state(selected.into(), time);
+        let error = ball_body.translation().z - start.z;
+        let hit = tuning.shots.get(selected).and_then(|shot_tuning| {
+            Contact::new(error, shot_tuning.timing_window, time, tuning)
+                .map(|hit| (shot_tuning, hit))
+        });
+        let Some((shot_tuning, hit)) = hit else {
+            return;
+        };
+        let swing_speed = if selected == Shot::Block {
+            tuning.batting_speed_threshold
+        } else {
+            speed
+        };
+        let direction = Quat::from_axis_angle(Vec3::Y, *batting_direction)
+            * Quat::from_axis_angle(Vec3::X, shot_tuning.elevation)
+            * Vec3::NEG_Z;
+        let incoming = Vec3::from(*ball_body.linvel());
+        let velocity = if hit.edged {
+            // The ball carries on past the bat, deflected a little to either side.
+            let angle = tuning.timing.edge_angle * random::<f32>().mul_add(2., -1.);
+            Quat::from_axis_angle(Vec3::Y, angle) * incoming * tuning.timing.edge_speed
+        } else {
+            let velocity = direction * swing_speed * shot_tuning.power;
+            incoming
+                + (velocity - incoming)
+                    * tuning
+                        .timing
+                        .velocity_multiplier
+                        .evaluate(error.abs() / shot_tuning.timing_window)
+                        .clamp(0., 1.)
+        };
+        ball_body.set_linvel(Vector3::from(velocity), true);
+        teams.batting.timings.push(hit.timing);
+        *contact = Some(hit);
+        *ball_hit = true;
+        *predicted_path = None;
+    }
+
+    /// Once the ball is hit or reaches the batter, the umpire makes their call and signals it.
+    fn call_delivery(&mut self, time: f32, tuning: &Tuning) {
+        let State::Playing {
+            teams,
+            ball_thrown,
+            ball_hit,
+            umpire,
+            call,
+            called_at,
+            physics_stuff,
+            ball_rigidbody_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let ball_body = &physics_stuff.bodies[*ball_rigidbody_handle];
+        let reached_batter = ball_body.translation().z >= Self::BATTING_CREASE;
+        if !*ball_thrown || called_at.is_some() || !(*ball_hit || reached_batter) {
+            return;
+        }
+        // Playing at a wide and hitting it means it wasn't out of reach after all.
+        *call = call.filter(|&call| !(call == Call::Wide && *ball_hit));
+        let extras = teams.over.record(*call, tuning);
+        teams.batting.runs += extras;
+        teams.batting.extras += extras;
+        if call.is_none() {
+            teams.batting.balls += 1;
         }
+        *called_at = Some(time);
+        umpire.set_state(
+            match call {
+                Some(Call::NoBall(_)) => SpriteState::NoBall,
+                Some(Call::Wide) => SpriteState::Wide,
+                None => SpriteState::Stance,
+            },
+            time,
+        );
     }
 
-    poly_consts! {
-        const DISTANCE_ERROR_TO_VELOCITY_MULTIPLIER => y = -0.646388x ^ 3 + 1.91648x ^ 2 - 2.08701x + 1;
+    const PREDICTION_TIME: f32 = 4.;
+
+    /// Before the shot, marks where the ball on `path` is predicted to pitch and where it will
+    /// cross the batting crease. After it, marks where the ball is predicted to land.
+    fn draw_batting_aid(path: &[Vec3], ball_hit: bool) {
+        const PITCH_MARKER_SIZE: f32 = 0.3;
+        const LANDING_MARKER_SIZE: f32 = 1.5;
+        if ball_hit {
+            if let Some((_, landing)) = trajectory::bounce(path) {
+                Self::draw_marker(landing, LANDING_MARKER_SIZE, Self::LANDING_COLOUR.themed());
+            }
+            return;
+        }
+        let (pitch, arrival) = trajectory::delivery(path, Self::BATTING_CREASE);
+        let Some(arrival) = arrival else {
+            return;
+        };
+        if let Some(pitch) = pitch {
+            Self::draw_marker(pitch, PITCH_MARKER_SIZE, Self::AID_COLOUR.themed());
+        }
+        draw_sphere_wires(
+            arrival,
+            Self::BALL_RADIUS * 2.,
+            None,
+            Self::AID_COLOUR.themed(),
+        );
+    }
+
+    /// A flat square on the ground under `position`.
+    fn draw_marker(position: Vec3, size: f32, colour: Color) {
+        const HEIGHT: f32 = 0.01;
+        draw_cube(
+            vec3(position.x, HEIGHT, position.z),
+            vec3(size, 0., size),
+            None,
+            colour,
+        );
     }
-    const BATTING_SPEED_THRESHOLD: f32 = 5.;
-    const BATTING_DIRECTION_LIMIT: f32 = 2.5;
-    const DELTA_MULTIPLIER: Vec2 = vec2(0.2, 0.05);
+
+    const AID_COLOUR: Colour = Colour::Glow;
+    const LANDING_COLOUR: Colour = Colour::Candle;
+
+    /// Wireframes of every collider, and the ball's velocity, spin and predicted `path` once it's
+    /// released.
+    fn draw_physics_debug(
+        physics_stuff: &PhysicsStuff,
+        ball_body_handle: RigidBodyHandle,
+        path: Option<&[Vec3]>,
+    ) {
+        debug::draw_colliders(&physics_stuff.colliders, Self::COLLIDER_COLOUR.themed());
+        let ball = &physics_stuff.bodies[ball_body_handle];
+        let position = Vec3::from(*ball.translation());
+        draw_line_3d(
+            position,
+            position + Vec3::from(*ball.linvel()),
+            Self::VELOCITY_COLOUR.themed(),
+        );
+        draw_line_3d(
+            position,
+            position + Vec3::from(*ball.angvel()),
+            Self::SPIN_COLOUR.themed(),
+        );
+        if let Some(path) = path {
+            debug::draw_path(path, Self::PREDICTION_COLOUR.themed());
+        }
+    }
+
+    const COLLIDER_COLOUR: Colour = Colour::Ion;
+    const VELOCITY_COLOUR: Colour = Colour::Candle;
+    const SPIN_COLOUR: Colour = Colour::Blood;
+    const PREDICTION_COLOUR: Colour = Colour::Glow;
 
     fn draw_sides() {
         for side in [-1., 1.] {
@@ -649,7 +1669,7 @@ impl<'n> Game<'n> {
                     0.,
                     side * (Self::BETWEEN_WICKETS / 2. - Self::BOWLING_CREASE_TO_POPPING_CREASE),
                 ),
-                Self::LINE_COLOUR,
+                Self::LINE_COLOUR.themed(),
             );
             draw_line_3d(
                 vec3(
@@ -662,7 +1682,7 @@ impl<'n> Game<'n> {
                     0.,
                     side * (Self::BETWEEN_WICKETS / 2.),
                 ),
-                Self::LINE_COLOUR,
+                Self::LINE_COLOUR.themed(),
             );
             for return_crease in [-1., 1.] {
                 draw_line_3d(
@@ -677,7 +1697,7 @@ impl<'n> Game<'n> {
                         0.,
                         side * (Self::BETWEEN_WICKETS / 2. + Self::BOWLING_CREASE_TO_END),
                     ),
-                    Self::LINE_COLOUR,
+                    Self::LINE_COLOUR.themed(),
                 );
             }
         }
@@ -685,11 +1705,9 @@ impl<'n> Game<'n> {
 
     fn draw_showing_coin_result(&mut self) {
         const TEXTURE_SIZE: f32 = 40.;
-        const TEXT_GAP: f32 = 2.;
         const TEXT_SIZE: u16 = 2;
-        const X_GAP: f32 = 100.;
+        const COLUMN_GAP: f32 = 10.;
         const HEADING_TEXT_GAP: f32 = 10.;
-        let [heading_style, text_style] = self.skins([Self::HEADING_TEXT_SIZE, TEXT_SIZE]);
         let State::ShowingCoinResult {
             bet,
             result,
@@ -698,76 +1716,115 @@ impl<'n> Game<'n> {
         else {
             unreachable!()
         };
-        Self::window(|ui| {
-            if bet == result {
-                self.draw_choose_role(ui, &text_style, TEXTURE_SIZE, X_GAP, TEXT_GAP, TEXT_SIZE);
-            }
-            ui.push_skin(&heading_style);
-            Texture::new(result.texture())
-                .position(Self::transform_size(
-                    Self::SIZE / 2. - Vec2::splat(TEXTURE_SIZE / 2.),
-                ))
-                .size(
-                    Self::transform_length(TEXTURE_SIZE),
-                    Self::transform_length(TEXTURE_SIZE),
-                )
-                .ui(ui);
-            let text = format!("{result}!");
-            let dimensions = self.text_measurer.measure(TextMeasureInput {
-                text: text.clone(),
-                size: Self::transform_length(Self::HEADING_TEXT_SIZE as f32) as u16,
-            });
-            ui.label(
-                Self::transform_size(vec2(
-                    Self::SIZE.x / 2. - Self::untransform_length(dimensions.width / 2.),
-                    Self::SIZE.y / 2. + TEXTURE_SIZE / 2. + HEADING_TEXT_GAP,
-                )),
-                &text,
-            );
-            let text = if bet == result {
-                "Choose to bat or field first".to_string()
-            } else {
-                format!(
-                    "Opponent chose to {} first! Click to continue",
-                    match opponent_choice {
-                        Role::Batting => "bat",
-                        Role::Fielding => "field",
-                    }
+        let text = if bet == result {
+            "Choose to bat or field first".to_string()
+        } else {
+            format!(
+                "Opponent chose to {} first! Click to continue",
+                match opponent_choice {
+                    Role::Batting => "bat",
+                    Role::Fielding => "field",
+                }
+            )
+        };
+        let result_column = Node::column(
+            Self::LINE_GAP,
+            [
+                Node::texture(result.texture(&self.assets), Vec2::splat(TEXTURE_SIZE)),
+                Node::space(vec2(0., HEADING_TEXT_GAP - 2. * Self::LINE_GAP)),
+                Node::text(format!("{result}!"), Self::HEADING_TEXT_SIZE),
+                Node::wrapped(
+                    text,
+                    TEXT_SIZE,
+                    if bet == result {
+                        TEXTURE_SIZE
+                    } else {
+                        Self::SIZE.x
+                    },
+                ),
+            ],
+        );
+        let root = if bet == result {
+            let role_button = |role: Role, text| {
+                Node::column(
+                    Self::LINE_GAP,
+                    [
+                        Node::texture(role.texture(&self.assets), Vec2::splat(TEXTURE_SIZE)),
+                        Node::wrapped(text, TEXT_SIZE, TEXTURE_SIZE),
+                    ],
                 )
+                .padding(Self::BUTTON_PADDING)
+                .button(role as usize)
             };
-            ui.push_skin(&text_style);
-            let sub_dimensions = self.text_measurer.measure(TextMeasureInput {
-                text: text.clone(),
-                size: Self::transform_length(TEXT_SIZE as f32) as u16,
-            });
-            ui.label(
-                Self::transform_size(vec2(
-                    Self::SIZE.x / 2. - Self::untransform_length(sub_dimensions.width / 2.),
-                    Self::SIZE.y / 2.
-                        + TEXTURE_SIZE / 2.
-                        + HEADING_TEXT_GAP
-                        + Self::untransform_length(dimensions.height)
-                        + TEXT_GAP,
-                )),
-                &text,
+            Node::row(
+                COLUMN_GAP,
+                [
+                    role_button(Role::Batting, "You bat first\nOpponent fields first"),
+                    result_column,
+                    role_button(Role::Fielding, "You field first\nOpponent bats first"),
+                ],
+            )
+        } else {
+            result_column.padding(Self::BUTTON_PADDING).button(0)
+        };
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(
+                ui,
+                &root,
+                Anchor::Top,
+                vec2(
+                    0.,
+                    Self::SIZE.y / 2. - TEXTURE_SIZE / 2. - Self::BUTTON_PADDING,
+                ),
             );
         });
-        if is_mouse_button_released(MouseButton::Left) {
-            self.state = Self::init_playing_state(if bet == result {
-                match ScreenSide::from_mouse_position() {
-                    ScreenSide::Left => Teams::new(["You", "Opponent"]),
-                    ScreenSide::Right => Teams::new(["Opponent", "You"]),
-                }
+        if let Some(id) = activated {
+            let you_bat = if bet == result {
+                id == Role::Batting as usize
             } else {
-                match opponent_choice {
-                    Role::Batting => Teams::new(["Opponent", "You"]),
-                    Role::Fielding => Teams::new(["You", "Opponent"]),
-                }
-            });
+                opponent_choice == Role::Fielding
+            };
+            self.state = Self::init_playing_state(self.match_teams(you_bat), &self.assets);
         }
     }
 
-    fn init_playing_state(teams: Teams<'n>) -> State<'n> {
+    /// The two sides for a match, the player's batting first if `you_bat`. In a tournament
+    /// fixture or career match the opponent is whoever the player was drawn against.
+    fn match_teams(&self, you_bat: bool) -> Teams<'n> {
+        let overs = tuning::current().overs;
+        let (opponent, overs) = match (self.competition, &self.tournament, &self.career) {
+            (Some(Competition::Tournament(fixture)), Some(tournament), _) => {
+                let teams = tournament.fixtures[fixture].teams;
+                let opponent = teams[usize::from(teams[0] == PLAYER)];
+                (TEAM_NAMES[opponent], tournament.overs)
+            }
+            (Some(Competition::Career), _, Some(profile)) => profile
+                .season
+                .next_match()
+                .map_or(("Opponent", overs), |next| {
+                    (TEAM_NAMES[profile.season.calendar[next].opponent], overs)
+                }),
+            _ => ("Opponent", overs),
+        };
+        let you = TEAM_NAMES[PLAYER];
+        Teams::new(
+            if you_bat {
+                [you, opponent]
+            } else {
+                [opponent, you]
+            },
+            overs,
+        )
+    }
+
+    /// How far the release point can move from one delivery to the next.
+    const RELEASE_VARIATION: f32 = 0.2;
+    /// How far ahead the umpire predicts a delivery to judge it.
+    const JUDGING_TIME: f32 = 4.;
+
+    fn init_playing_state(teams: Teams<'n>, assets: &Assets) -> State<'n> {
+        let tuning = tuning::current();
         let mut bodies = RigidBodySet::new();
         let mut colliders = ColliderSet::new();
         colliders.insert(
@@ -776,18 +1833,19 @@ impl<'n> Game<'n> {
                 .position(vector![0., -1., 0.].into())
                 .build(),
         );
-        let ball_start = vector![
-            0.5,
-            2.4,
-            -Game::BETWEEN_WICKETS / 2. + Game::BOWLING_CREASE_TO_POPPING_CREASE / 2.,
-        ] + Vector3::from(random_in_unit_sphere()) * 0.2;
+        let free_hit = teams.over.free_hit;
+        let release_variation = random_in_unit_sphere() * Self::RELEASE_VARIATION;
+        let stride = rand::thread_rng()
+            .gen_range(-tuning.umpire.stride_variation..=tuning.umpire.stride_variation);
+        let bowler_position = Self::bowler_position(0., stride, &tuning);
+        let ball_start = bowler_position + tuning.release_offset + release_variation;
 
         let ball_rigidbody_handle =
-            bodies.insert(RigidBodyBuilder::dynamic().translation(ball_start));
+            bodies.insert(RigidBodyBuilder::dynamic().translation(ball_start.into()));
         colliders.insert_with_parent(
             ColliderBuilder::ball(Self::BALL_RADIUS)
                 .restitution(1.)
-                .mass(0.1559)
+                .mass(tuning.ball_mass)
                 .build(),
             ball_rigidbody_handle,
             &mut bodies,
@@ -805,18 +1863,49 @@ impl<'n> Game<'n> {
         let ccd_solver = CCDSolver::new();
         let query_pipeline = QueryPipeline::new();
 
+        let time = get_time() as f32;
+        let fielders = Self::FIELDER_POSITIONS
+            .into_iter()
+            .map(|position| Fielder {
+                sprite: AnimatedSprite::new(assets.fielder.clone(), Self::BATTER_SIZE, time),
+                position,
+            })
+            .collect();
+
         State::Playing {
-            innings: 0,
             teams,
 
-            start: get_time() as f32,
+            start: time,
 
             batting_direction: 0.,
             ball_thrown: false,
+            shot: None,
+            contact: None,
             ball_hit: false,
+            predicted_path: None,
 
-            camera_position: Self::POSITION,
-            camera_target: Self::TARGET,
+            camera_position: tuning.camera_position,
+            camera_target: tuning.camera_target,
+
+            batter: AnimatedSprite::new(assets.batter.clone(), Self::BATTER_SIZE, time),
+            bowler: Fielder {
+                sprite: AnimatedSprite::new(assets.bowler.clone(), Self::BATTER_SIZE, time),
+                position: bowler_position,
+            },
+            release_variation,
+            stride,
+            umpire: AnimatedSprite::new(assets.umpire.clone(), Self::BATTER_SIZE, time),
+            call: None,
+            called_at: None,
+            free_hit,
+            fielders,
+
+            run: 0.,
+            thrown_in: false,
+            frames: Vec::new(),
+            tracking: None,
+            appeal: None,
+            dead_at: None,
 
             physics_stuff: PhysicsStuff {
                 bodies,
@@ -836,166 +1925,128 @@ impl<'n> Game<'n> {
         }
     }
 
-    fn draw_choose_role(
+    /// Draws a layout into the current window, returning the id of any button activated this frame.
+    fn draw_layout(
         &mut self,
         ui: &mut Ui,
-        text_style: &Skin,
-        texture_size: f32,
-        x_gap: f32,
-        text_gap: f32,
-        text_size: u16,
-    ) {
-        ui.push_skin(text_style);
-        let position = Self::transform_point(vec2(
-            match mouse_position_local().x.total_cmp(&0.) {
-                Ordering::Less | Ordering::Equal => 0.,
-                Ordering::Greater => Self::SIZE.x / 2.,
-            },
-            0.,
-        ));
-        let size = Self::transform_size(vec2(Self::SIZE.x / 2., Self::SIZE.y));
-        ui.canvas().rect(
-            Rect::new(position.x, position.y, size.x, size.y),
-            None,
-            Self::HIGHLIGHT_COLOUR,
+        root: &Node,
+        anchor: Anchor,
+        offset: Vec2,
+    ) -> Option<usize> {
+        let placed = root.layout(Self::SIZE, anchor, offset, |text, size, max_width| {
+            self.text_block(text, size, max_width, Self::LINE_GAP)
+        });
+        let buttons = placed
+            .iter()
+            .filter_map(|placed| match *placed {
+                Placed::Button { id, rect } => Some((id, rect)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut input = NavigationInput::read(
+            Self::untransform_point(mouse_position().into()),
+            &self.gamepads,
         );
-
-        for (x_side, role, lines) in [
-            (
-                -1.,
-                Role::Batting,
-                ["You bat first", "Opponent fields first"],
-            ),
-            (
-                1.,
-                Role::Fielding,
-                ["You field first", "Opponent bats first"],
-            ),
-        ] {
-            let total_height = text_gap.mul_add(2., texture_size)
-                + Self::untransform_length(
-                    lines
-                        .into_iter()
-                        .map(|line| {
-                            self.text_measurer
-                                .measure(TextMeasureInput {
-                                    text: line.to_string(),
-                                    size: Self::transform_length(text_size as f32) as u16,
-                                })
-                                .height
-                        })
-                        .sum(),
-                );
-            Texture::new(role.texture())
-                .position(Self::transform_size(vec2(
-                    Self::SIZE.x / 2. + x_side * x_gap / 2. - texture_size / 2.,
-                    Self::SIZE.y / 2. - total_height / 2.,
-                )))
-                .size(
-                    Self::transform_length(texture_size),
-                    Self::transform_length(texture_size),
-                )
-                .ui(ui);
-
-            let mut total_text_height = 0.;
-            for (index, line) in lines.into_iter().enumerate() {
-                let dimensions = self.text_measurer.measure(TextMeasureInput {
-                    text: line.to_string(),
-                    size: Self::transform_length(text_size as f32) as u16,
-                });
-                ui.label(
-                    Self::transform_size(vec2(
-                        Self::SIZE.x / 2. + x_side * x_gap / 2.
-                            - Self::untransform_length(dimensions.width / 2.),
-                        (index as f32).mul_add(
-                            text_gap,
-                            Self::SIZE.y / 2. - total_height / 2. + texture_size + text_gap,
-                        ) + total_text_height
-                            - Self::untransform_length(dimensions.height / 2.),
-                    )),
-                    line,
-                );
-                total_text_height += Self::untransform_length(dimensions.height);
+        if self.console.open {
+            // Typing into the console shouldn't also press buttons.
+            input.previous = false;
+            input.next = false;
+            input.activate = false;
+        }
+        let activated = self.buttons.update(&buttons, input);
+        let mut skins = HashMap::new();
+        for placed in placed {
+            match placed {
+                Placed::Button { id, rect } => {
+                    let colour = match self.buttons.state(id) {
+                        ButtonState::Idle => continue,
+                        ButtonState::Focused => Self::HIGHLIGHT_COLOUR.themed(),
+                        ButtonState::Pressed => Self::PRESSED_COLOUR.themed(),
+                    };
+                    let position = Self::transform_point(rect.point());
+                    let size = Self::transform_size(rect.size());
+                    ui.canvas().rect(
+                        Rect::new(position.x, position.y, size.x, size.y),
+                        None,
+                        colour,
+                    );
+                }
+                Placed::Texture { texture, rect } => {
+                    let size = Self::transform_size(rect.size());
+                    Texture::new(texture)
+                        .position(Self::transform_size(rect.point()))
+                        .size(size.x, size.y)
+                        .ui(ui);
+                }
+                Placed::Text {
+                    block,
+                    size,
+                    anchor,
+                } => {
+                    let skin = skins.entry(size).or_insert_with(|| {
+                        let [skin] = self.skins([size]);
+                        skin
+                    });
+                    ui.push_skin(skin);
+                    Self::draw_text_block(ui, &block, anchor);
+                    ui.pop_skin();
+                }
             }
         }
-        ui.pop_skin();
+        activated
     }
 
     const SIZE: Vec2 = vec2(160., 100.);
 
     fn draw_flipping_coin(&mut self) {
-        const ANIMATION_FRAMES_PER_SECOND: f32 = 10.;
         const TOP: f32 = 10.;
         const BOTTOM: f32 = 10.;
-        const EXTRA_FRAMES: usize = 3;
+        const EXTRA_TIME: f32 = 0.3;
         let State::FlippingCoin { bet, start } = self.state else {
             unreachable!()
         };
         Self::window(|ui| {
-            fn inner<const N: usize>(
-                textures: [Texture2D; N],
-                f: impl FnOnce([Texture2D; N], usize),
-            ) {
-                f(textures, N);
-            }
-            inner(include_textures!("coin-flip", 1..=18), |textures, len| {
-                let index = (Instant::now()
-                    .saturating_duration_since(start)
-                    .as_secs_f32()
-                    * ANIMATION_FRAMES_PER_SECOND) as usize;
-                let texture = textures.into_iter().nth(index.min(len - 1)).unwrap();
-                let height = Self::SIZE.y - TOP - BOTTOM;
-                let width = height * texture.width() / texture.height();
-                let size = Self::transform_size(vec2(width, height));
-                Texture::new(texture)
-                    .position(Self::transform_size(vec2(
-                        Self::SIZE.x / 2. - width / 2.,
-                        TOP,
-                    )))
-                    .size(size.x, size.y)
-                    .ui(ui);
-                if index >= len + EXTRA_FRAMES {
-                    self.state = State::ShowingCoinResult {
-                        bet,
-                        result: random(),
-                        opponent_choice: random(),
-                    }
+            let animation = &self.assets.coin_flip;
+            let time = Instant::now()
+                .saturating_duration_since(start)
+                .as_secs_f32();
+            let texture = animation.frame(time, Playback::Once).clone();
+            let height = Self::SIZE.y - TOP - BOTTOM;
+            let width = height * texture.width() / texture.height();
+            let size = Self::transform_size(vec2(width, height));
+            Texture::new(texture)
+                .position(Self::transform_size(vec2(
+                    Self::SIZE.x / 2. - width / 2.,
+                    TOP,
+                )))
+                .size(size.x, size.y)
+                .ui(ui);
+            if animation.is_finished(time - EXTRA_TIME, Playback::Once) {
+                self.state = State::ShowingCoinResult {
+                    bet,
+                    result: random(),
+                    opponent_choice: random(),
                 }
-            });
+            }
         });
     }
 
     fn draw_tossing_coin(&mut self) {
         const TEXTURE_SIZE: f32 = 40.;
         const TEXTURE_TOP: f32 = 50.;
-        const TEXT_GAP: f32 = 2.;
-        let [text_style] = self.skins([Self::TEXT_SIZE]);
+        let root = Node::column(
+            Self::LINE_GAP,
+            [
+                Node::texture(self.assets.coin_drag.clone(), Vec2::splat(TEXTURE_SIZE)),
+                Node::text("Swipe up to flip", Self::TEXT_SIZE),
+            ],
+        );
+        Self::window(|ui| {
+            self.draw_layout(ui, &root, Anchor::Top, vec2(0., TEXTURE_TOP));
+        });
         let State::TossingCoin { bet, mouse_down_y } = &mut self.state else {
             unreachable!()
         };
-        Self::window(|ui| {
-            ui.push_skin(&text_style);
-            let texture = include_texture!("coin-drag");
-            let position =
-                Self::transform_size(vec2(Self::SIZE.x / 2. - TEXTURE_SIZE / 2., TEXTURE_TOP));
-            let size = Self::transform_size(Vec2::splat(TEXTURE_SIZE));
-            Texture::new(texture)
-                .position(position)
-                .size(size.x, size.y)
-                .ui(ui);
-            let text = "Swipe up to flip";
-            let dimensions = self.text_measurer.measure(TextMeasureInput {
-                text: text.to_string(),
-                size: Self::transform_length(Self::TEXT_SIZE as f32) as u16,
-            });
-            ui.label(
-                vec2(
-                    Self::transform_length(Self::SIZE.x) / 2. - dimensions.width / 2.,
-                    Self::transform_length(TEXTURE_TOP + TEXTURE_SIZE + TEXT_GAP),
-                ),
-                text,
-            );
-        });
         if is_mouse_button_pressed(MouseButton::Left) {
             *mouse_down_y = Some(mouse_position_local().y);
         }
@@ -1050,92 +2101,319 @@ impl<'n> Game<'n> {
                 .with_font(&self.font)
                 .unwrap()
                 .font_size(Self::transform_length(size as f32) as u16)
-                .background(Image::gen_image_color(1, 1, Self::BACKGROUND_COLOUR))
+                .background(Image::gen_image_color(
+                    1,
+                    1,
+                    Self::BACKGROUND_COLOUR.themed(),
+                ))
                 .build();
             Self::make_skin(&style)
         })
     }
 
-    const BACKGROUND_COLOUR: Color = colour!(White);
+    const BACKGROUND_COLOUR: Colour = Colour::White;
 
     const HEADING_TEXT_SIZE: u16 = 10;
     const TEXT_SIZE: u16 = 5;
-    const HIGHLIGHT_COLOUR: Color = colour!(Birch);
+    const LINE_GAP: f32 = 2.;
+    const HIGHLIGHT_COLOUR: Colour = Colour::Birch;
+    const PRESSED_COLOUR: Colour = Colour::Oak;
+    const BUTTON_PADDING: f32 = 3.;
+
+    /// Toggles the overlay with F3, runs console commands, and draws whichever of the two is open
+    /// on top of everything else.
+    fn draw_debug(&mut self) {
+        const MARGIN: f32 = 2.;
+        const TEXT_SIZE: u16 = 3;
+        if is_key_pressed(KeyCode::F3) {
+            self.debug_overlay = !self.debug_overlay;
+        }
+        if let Some(command) = self.console.update() {
+            self.run_command(command);
+        }
+        let mut lines = Vec::new();
+        if self.debug_overlay {
+            let vector = |vector: Vec3| format!("{:.1} {:.1} {:.1}", vector.x, vector.y, vector.z);
+            lines.push(format!("FPS {}", get_fps()));
+            lines.push(format!("State {}", <&str>::from(&self.state)));
+            if let State::Playing {
+                teams,
+                shot,
+                contact,
+                physics_stuff,
+                ball_rigidbody_handle,
+                ..
+            } = &self.state
+            {
+                let ball = &physics_stuff.bodies[*ball_rigidbody_handle];
+                let batting = &teams.batting;
+                lines.push(format!(
+                    "{} {}/{}",
+                    batting.name, batting.runs, batting.wickets
+                ));
+                let over = &teams.over;
+                lines.push(format!(
+                    "Ball {}/{} bouncers {}",
+                    over.legal_balls,
+                    Over::BALLS,
+                    over.bouncers
+                ));
+                lines.push(format!(
+                    "Reviews {} {}",
+                    teams.batting.reviews, teams.fielding.reviews
+                ));
+                lines.push(format!("Velocity {}", vector((*ball.linvel()).into())));
+                lines.push(format!("Spin {}", vector((*ball.angvel()).into())));
+                if let Some(shot) = shot {
+                    lines.push(format!("Shot {shot}"));
+                }
+                if let Some(contact) = contact {
+                    let edged = if contact.edged { ", edged" } else { "" };
+                    lines.push(format!("Contact {}{edged}", contact.timing));
+                }
+            }
+        }
+        if self.console.open {
+            lines.extend(self.console.log.iter().cloned());
+            lines.push(format!("> {}_", self.console.input));
+        }
+        if lines.is_empty() {
+            return;
+        }
 
-    fn draw_picking_side(&mut self) {
-        const GAP: f32 = 80.;
-        const HEADING_TOP: f32 = 10.;
-        const TEXTURE_TOP: f32 = 30.;
-        const TEXT_GAP: f32 = 2.;
-        const TEXTURE_SIZE: f32 = 40.;
-        let [heading_style, text_style] = self.skins([Self::HEADING_TEXT_SIZE, Self::TEXT_SIZE]);
+        let line_height = TEXT_SIZE as f32 + Self::LINE_GAP;
+        let style = root_ui()
+            .style_builder()
+            .with_font(&self.font)
+            .unwrap()
+            .font_size(Self::transform_length(TEXT_SIZE as f32) as u16)
+            .text_color(colour!(White))
+            .background(Image::gen_image_color(1, 1, Self::DEBUG_BACKGROUND))
+            .build();
+        let skin = Self::make_skin(&style);
+        let id = hash!();
+        let size = vec2(
+            Self::SIZE.x,
+            (lines.len() as f32).mul_add(line_height, 2. * MARGIN),
+        );
+        root_ui().push_skin(&skin);
+        Window::new(
+            id,
+            Self::transform_point(Vec2::ZERO),
+            Self::transform_size(size),
+        )
+        .titlebar(false)
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            for (index, line) in lines.iter().enumerate() {
+                ui.label(
+                    Self::transform_size(vec2(MARGIN, (index as f32).mul_add(line_height, MARGIN))),
+                    line,
+                );
+            }
+        });
+        root_ui().pop_skin();
+        // Windows are drawn after everything else, so this is the only way to stay on top of menus.
+        root_ui().focus_window(id);
+    }
 
-        Self::window(|ui| {
-            let position = Self::transform_point(vec2(
-                match ScreenSide::from_mouse_position() {
-                    ScreenSide::Left => 0.,
-                    ScreenSide::Right => Self::SIZE.x / 2.,
-                },
-                0.,
-            ));
-            let size = Self::transform_size(vec2(Self::SIZE.x / 2., Self::SIZE.y));
-            ui.canvas().rect(
-                Rect::new(position.x, position.y, size.x, size.y),
-                None,
-                Self::HIGHLIGHT_COLOUR,
-            );
+    const DEBUG_BACKGROUND: Color = hex!(#00000099);
+
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Help => {
+                for usage in debug::USAGES {
+                    self.console.print(usage);
+                }
+            }
+            Command::Deliver => {
+                let teams = match &self.state {
+                    State::Playing { teams, .. } | State::Reviewing { teams, .. } => teams.clone(),
+                    _ => self.match_teams(true),
+                };
+                self.state = Self::init_playing_state(teams, &self.assets);
+            }
+            Command::Score { runs, wickets } => {
+                if let State::Playing { teams, .. } | State::Reviewing { teams, .. } =
+                    &mut self.state
+                {
+                    teams.batting.runs = runs;
+                    teams.batting.wickets = wickets;
+                } else {
+                    self.console.print("no match is being played");
+                }
+            }
+            Command::Goto(screen) => {
+                self.state = match screen {
+                    Screen::PickingSide => State::PickingSide,
+                    Screen::TossingCoin => State::TossingCoin {
+                        bet: random(),
+                        mouse_down_y: None,
+                    },
+                    Screen::FlippingCoin => State::FlippingCoin {
+                        bet: random(),
+                        start: Instant::now(),
+                    },
+                    Screen::ShowingCoinResult => State::ShowingCoinResult {
+                        bet: random(),
+                        result: random(),
+                        opponent_choice: random(),
+                    },
+                    Screen::Tournament => State::Tournament {
+                        teams: Self::TOURNAMENT_TEAMS[0],
+                    },
+                    Screen::Career => State::Career { resetting: false },
+                    Screen::Nets => State::Nets { result: None },
+                    Screen::Playing => {
+                        Self::init_playing_state(self.match_teams(true), &self.assets)
+                    }
+                };
+            }
+        }
+    }
 
-            ui.push_skin(&heading_style);
-            let text = "Pick a side";
-            let dimensions = self.text_measurer.measure(TextMeasureInput {
-                text: text.to_string(),
-                size: Self::transform_length(Self::HEADING_TEXT_SIZE as f32) as u16,
+    fn draw_settings(&mut self) {
+        const HEADING_TOP: f32 = 10.;
+        const GAP: f32 = 2.;
+        show_mouse(true);
+        set_cursor_grab(false);
+        let on_off = |on| if on { "On" } else { "Off" };
+        let options = [
+            ("Integer scaling", display::integer_scaling()),
+            ("Scanlines", self.post_process.scanlines),
+            ("Curvature", self.post_process.curvature),
+            ("Dithering", self.post_process.dithering),
+            ("Batting aid", self.batting_aid),
+        ];
+        let buttons = options
+            .into_iter()
+            .map(|(name, on)| format!("{name}: {}", on_off(on)))
+            .chain([
+                format!("Palette: {}", palette::active().name),
+                "Tournament".to_string(),
+                "Career".to_string(),
+                "Nets".to_string(),
+                "Back".to_string(),
+            ])
+            .enumerate()
+            .map(|(id, text)| {
+                Node::text(text, Self::TEXT_SIZE)
+                    .padding(Self::BUTTON_PADDING)
+                    .button(id)
             });
-            ui.label(
-                vec2(
-                    Self::transform_length(Self::SIZE.x) / 2. - dimensions.width / 2.,
-                    HEADING_TOP + dimensions.height,
-                ),
-                "Pick a side",
-            );
+        let root = Node::column(
+            GAP,
+            iter::once(Node::text("Settings", Self::HEADING_TEXT_SIZE)).chain(buttons),
+        );
 
-            ui.pop_skin();
-            ui.push_skin(&text_style);
-            for (position, side) in [(-1., CoinSide::Heads), (1., CoinSide::Tails)] {
-                Texture::new(side.texture())
-                    .position(Self::transform_size(vec2(
-                        Self::SIZE.x / 2. + position * GAP / 2. - TEXTURE_SIZE / 2.,
-                        TEXTURE_TOP,
-                    )))
-                    .size(
-                        Self::transform_length(TEXTURE_SIZE),
-                        Self::transform_length(TEXTURE_SIZE),
-                    )
-                    .ui(ui);
-                let dimensions = self.text_measurer.measure(TextMeasureInput {
-                    text: side.to_string(),
-                    size: Self::transform_length(Self::TEXT_SIZE as f32) as u16,
-                });
-                ui.label(
-                    vec2(
-                        Self::transform_length(Self::SIZE.x / 2. + position * GAP / 2.)
-                            - dimensions.width / 2.,
-                        Self::transform_length(TEXTURE_TOP + TEXTURE_SIZE + TEXT_GAP),
-                    ),
-                    &side.to_string(),
-                );
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Top, vec2(0., HEADING_TOP));
+        });
+        match activated {
+            Some(0) => display::set_integer_scaling(!display::integer_scaling()),
+            Some(1) => self.post_process.scanlines = !self.post_process.scanlines,
+            Some(2) => self.post_process.curvature = !self.post_process.curvature,
+            Some(3) => self.post_process.dithering = !self.post_process.dithering,
+            Some(4) => self.batting_aid = !self.batting_aid,
+            Some(5) => {
+                palette::set_active(palette::active_index() + 1);
+                self.post_process.set_palette(&palette::active().colours);
             }
-
-            if is_mouse_button_released(MouseButton::Left) {
-                self.state = State::TossingCoin {
-                    bet: match mouse_position_local().x.total_cmp(&0.) {
-                        Ordering::Less | Ordering::Equal => CoinSide::Heads,
-                        Ordering::Greater => CoinSide::Tails,
+            Some(id @ 6..=8) => {
+                self.settings_open = false;
+                // Abandoning the nets or the match, so it doesn't count towards a fixture.
+                self.nets.session = None;
+                self.competition = None;
+                self.state = match id {
+                    6 => State::Tournament {
+                        teams: Self::TOURNAMENT_TEAMS[0],
                     },
-                    mouse_down_y: None,
-                }
+                    7 => State::Career { resetting: false },
+                    _ => State::Nets { result: None },
+                };
+            }
+            Some(_) => {
+                self.settings_open = false;
+                self.buttons = Buttons::default();
             }
+            None => {}
+        }
+    }
+
+    fn draw_showing_match_result(&mut self) {
+        const HEADING_GAP: f32 = 10.;
+        let State::ShowingMatchResult {
+            ref summary,
+            competition,
+        } = self.state
+        else {
+            unreachable!()
+        };
+        let root = Node::column(
+            HEADING_GAP,
+            [
+                Node::wrapped(summary.clone(), Self::HEADING_TEXT_SIZE, Self::SIZE.x),
+                Node::text("Continue", Self::TEXT_SIZE)
+                    .padding(Self::BUTTON_PADDING)
+                    .button(0),
+            ],
+        );
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Centre, Vec2::ZERO);
         });
+        if activated.is_some() {
+            self.state = match competition {
+                Some(Competition::Tournament(_)) => State::Tournament {
+                    teams: Self::TOURNAMENT_TEAMS[0],
+                },
+                Some(Competition::Career) => State::Career { resetting: false },
+                None => State::PickingSide,
+            };
+        }
+    }
+
+    fn draw_picking_side(&mut self) {
+        const SIDES: [CoinSide; 2] = [CoinSide::Heads, CoinSide::Tails];
+        const GAP: f32 = 34.;
+        const HEADING_TOP: f32 = 10.;
+        const HEADING_GAP: f32 = 5.;
+        const TEXTURE_SIZE: f32 = 40.;
+        let root = Node::column(
+            HEADING_GAP,
+            [
+                Node::text("Pick a side", Self::HEADING_TEXT_SIZE),
+                Node::row(
+                    GAP,
+                    SIDES.into_iter().enumerate().map(|(id, side)| {
+                        Node::column(
+                            Self::LINE_GAP,
+                            [
+                                Node::texture(
+                                    side.texture(&self.assets),
+                                    Vec2::splat(TEXTURE_SIZE),
+                                ),
+                                Node::text(side.to_string(), Self::TEXT_SIZE),
+                            ],
+                        )
+                        .padding(Self::BUTTON_PADDING)
+                        .button(id)
+                    }),
+                ),
+            ],
+        );
+
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Top, vec2(0., HEADING_TOP));
+        });
+        if let Some(id) = activated {
+            self.state = State::TossingCoin {
+                bet: SIDES[id],
+                mouse_down_y: None,
+            }
+        }
     }
 
     fn draw_stumps() {
@@ -1151,7 +2429,7 @@ impl<'n> Game<'n> {
                     Self::STUMP_DIAMETER / 2.,
                     Self::STUMP_HEIGHT,
                     None,
-                    Self::LINE_COLOUR,
+                    Self::LINE_COLOUR.themed(),
                 );
             }
         }
@@ -1159,7 +2437,12 @@ impl<'n> Game<'n> {
 
     fn draw_ball(position: Vec3) {
         draw_sphere(position, Self::BALL_RADIUS + 0.01, None, BLACK);
-        draw_sphere(position, Self::BALL_RADIUS, None, Self::BALL_COLOUR);
+        draw_sphere(
+            position,
+            Self::BALL_RADIUS,
+            None,
+            Self::BALL_COLOUR.themed(),
+        );
     }
 }
 
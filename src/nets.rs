@@ -0,0 +1,499 @@
+use crate::{
+    layout::{Anchor, Node},
+    tournament::{PLAYER, TEAM_NAMES},
+    tuning::{self, Tuning},
+    Game, State, Teams,
+};
+use macroquad::math::{vec2, vec3, Vec3};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{read_to_string, write},
+    io::ErrorKind,
+    iter,
+};
+use strum::{Display, VariantArray};
+
+/// Where the best drill scores are saved, relative to where the game is run from.
+const PATH: &str = "nets.toml";
+
+/// How the ball moves through the air.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, VariantArray)]
+pub enum Movement {
+    Straight,
+    /// Swings in towards the batter's legs.
+    Inswinger,
+    /// Swings away towards the off side.
+    Outswinger,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, VariantArray)]
+pub enum Speed {
+    Slow,
+    Medium,
+    Fast,
+}
+
+/// Where the ball crosses the batting crease.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, VariantArray)]
+pub enum Line {
+    #[strum(serialize = "Leg stump")]
+    Leg,
+    #[strum(serialize = "Middle stump")]
+    Middle,
+    #[strum(serialize = "Off stump")]
+    Off,
+    #[strum(serialize = "Outside off")]
+    OutsideOff,
+}
+
+/// Where the ball pitches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, VariantArray)]
+pub enum Length {
+    Yorker,
+    Full,
+    #[strum(serialize = "Good length")]
+    Good,
+    Short,
+    /// Doesn't pitch at all.
+    #[strum(serialize = "Full toss")]
+    FullToss,
+}
+
+/// The value after `value`, going back to the first after the last.
+pub fn cycle<T: VariantArray + PartialEq + Copy>(value: T) -> T {
+    let index = T::VARIANTS
+        .iter()
+        .position(|&variant| variant == value)
+        .unwrap_or_default();
+    T::VARIANTS[(index + 1) % T::VARIANTS.len()]
+}
+
+/// A delivery picked in the nets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setup {
+    pub movement: Movement,
+    pub speed: Speed,
+    pub line: Line,
+    pub length: Length,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self {
+            movement: Movement::Straight,
+            speed: Speed::Medium,
+            line: Line::Off,
+            length: Length::Good,
+        }
+    }
+}
+
+impl Setup {
+    /// The velocity to release the ball from `hand` at and the sideways force to keep on it, so it
+    /// pitches at the chosen length and crosses `crease` on the chosen line.
+    pub fn release(self, hand: Vec3, crease: f32, gravity: f32, tuning: &Tuning) -> (Vec3, Vec3) {
+        let nets = &tuning.nets;
+        let speed = match self.speed {
+            Speed::Slow => nets.slow_speed,
+            Speed::Medium => nets.medium_speed,
+            Speed::Fast => nets.fast_speed,
+        };
+        let line = match self.line {
+            Line::Leg => nets.leg_line,
+            Line::Middle => 0.,
+            Line::Off => nets.off_line,
+            Line::OutsideOff => nets.outside_off_line,
+        };
+        let swing = match self.movement {
+            Movement::Straight => 0.,
+            Movement::Inswinger => -nets.swing_force,
+            Movement::Outswinger => nets.swing_force,
+        };
+        let to_crease = (crease - hand.z) / speed;
+        let sideways =
+            (swing / tuning.ball_mass).mul_add(-to_crease * to_crease / 2., line - hand.x);
+        let pitch = match self.length {
+            Length::Yorker => Some(nets.yorker_length),
+            Length::Full => Some(nets.full_length),
+            Length::Good => Some(nets.good_length),
+            Length::Short => Some(nets.short_length),
+            Length::FullToss => None,
+        };
+        // Aimed at the ground where it pitches, or at the batter for a full toss.
+        let (height, time) = pitch.map_or((nets.full_toss_height, to_crease), |length| {
+            (0., (crease - length - hand.z) / speed)
+        });
+        let up = (height - hand.y) / time + gravity * time / 2.;
+        (vec3(sideways / to_crease, up, speed), vec3(swing, 0., 0.))
+    }
+}
+
+/// A scored set of deliveries practising one skill.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Display,
+    VariantArray,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Drill {
+    #[strum(serialize = "Cover drives")]
+    CoverDrives,
+    Yorkers,
+    #[strum(serialize = "Short ball")]
+    ShortBall,
+}
+
+impl Drill {
+    pub const fn goal(self) -> &'static str {
+        match self {
+            Self::CoverDrives => "Hit 10 through the covers",
+            Self::Yorkers => "Survive 6 yorkers",
+            Self::ShortBall => "Score off 6 short balls",
+        }
+    }
+
+    pub const fn balls(self) -> u32 {
+        match self {
+            Self::CoverDrives => 12,
+            Self::Yorkers | Self::ShortBall => 6,
+        }
+    }
+
+    /// The scores needed for one, two and three stars.
+    const fn star_scores(self) -> [u32; 3] {
+        match self {
+            Self::CoverDrives => [4, 7, 10],
+            Self::Yorkers => [2, 4, 6],
+            Self::ShortBall => [4, 8, 12],
+        }
+    }
+
+    pub fn stars(self, score: u32) -> usize {
+        self.star_scores()
+            .iter()
+            .filter(|&&needed| score >= needed)
+            .count()
+    }
+
+    fn setup<R: Rng + ?Sized>(self, rng: &mut R) -> Setup {
+        let mut pick = |choices: &[Setup]| *choices.choose(rng).unwrap();
+        let setup = |movement, speed, line, length| Setup {
+            movement,
+            speed,
+            line,
+            length,
+        };
+        match self {
+            Self::CoverDrives => pick(&[
+                setup(Movement::Straight, Speed::Medium, Line::Off, Length::Full),
+                setup(
+                    Movement::Straight,
+                    Speed::Medium,
+                    Line::OutsideOff,
+                    Length::Full,
+                ),
+                setup(Movement::Outswinger, Speed::Medium, Line::Off, Length::Full),
+            ]),
+            Self::Yorkers => pick(&[
+                setup(
+                    Movement::Straight,
+                    Speed::Fast,
+                    Line::Middle,
+                    Length::Yorker,
+                ),
+                setup(Movement::Inswinger, Speed::Fast, Line::Off, Length::Yorker),
+                setup(Movement::Straight, Speed::Fast, Line::Leg, Length::Yorker),
+            ]),
+            Self::ShortBall => pick(&[
+                setup(Movement::Straight, Speed::Fast, Line::Middle, Length::Short),
+                setup(Movement::Straight, Speed::Medium, Line::Off, Length::Short),
+            ]),
+        }
+    }
+
+    /// What one delivery was worth: shots through the covers, deliveries survived or runs scored.
+    fn score(self, ball: Ball) -> u32 {
+        if ball.out {
+            return 0;
+        }
+        match self {
+            Self::CoverDrives => u32::from(through_covers(ball.carry)),
+            Self::Yorkers => 1,
+            Self::ShortBall => ball.runs,
+        }
+    }
+}
+
+/// Radians from straight down the pitch, towards the off side, that cover starts and ends at.
+const COVERS: (f32, f32) = (0.5, 1.3);
+/// How far the ball has to get from the batter to count as having gone through the field.
+const COVER_DISTANCE: f32 = 10.;
+
+/// Whether a ball that got `carry` from the batter went through the covers.
+fn through_covers(carry: Vec3) -> bool {
+    // The batter faces towards negative z, with the off side towards positive x.
+    let angle = carry.x.atan2(-carry.z);
+    carry.length() >= COVER_DISTANCE && (COVERS.0..=COVERS.1).contains(&angle)
+}
+
+/// How one delivery went for the batter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Ball {
+    out: bool,
+    runs: u32,
+    /// The furthest the ball got from the batter along the ground.
+    carry: Vec3,
+}
+
+/// A stint in the nets, either bowling the picked delivery over and over or working through a
+/// drill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub drill: Option<Drill>,
+    /// What the next ball will be.
+    pub setup: Setup,
+    pub balls: u32,
+    pub score: u32,
+    /// The batting side's runs off the bat and wickets before the last ball, to tell what happened
+    /// on it.
+    runs: u32,
+    wickets: u32,
+}
+
+impl Session {
+    pub const fn practice(setup: Setup) -> Self {
+        Self {
+            drill: None,
+            setup,
+            balls: 0,
+            score: 0,
+            runs: 0,
+            wickets: 0,
+        }
+    }
+
+    pub fn drill<R: Rng + ?Sized>(drill: Drill, rng: &mut R) -> Self {
+        Self {
+            drill: Some(drill),
+            ..Self::practice(drill.setup(rng))
+        }
+    }
+
+    /// Scores the ball just bowled from the batting side's runs off the bat and wickets after it,
+    /// and picks the next one. Returns the drill's final score after its last ball.
+    pub fn bowled<R: Rng + ?Sized>(
+        &mut self,
+        runs: u32,
+        wickets: u32,
+        carry: Vec3,
+        rng: &mut R,
+    ) -> Option<(Drill, u32)> {
+        let ball = Ball {
+            out: wickets > self.wickets,
+            runs: runs - self.runs,
+            carry,
+        };
+        self.runs = runs;
+        self.wickets = wickets;
+        let drill = self.drill?;
+        self.balls += 1;
+        self.score += drill.score(ball);
+        self.setup = drill.setup(rng);
+        (self.balls >= drill.balls()).then_some((drill, self.score))
+    }
+}
+
+/// The best score in each drill, saved between sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Records {
+    pub best: BTreeMap<Drill, u32>,
+}
+
+impl Records {
+    pub fn record(&mut self, drill: Drill, score: u32) {
+        let best = self.best.entry(drill).or_default();
+        *best = (*best).max(score);
+    }
+
+    /// The saved records, or none yet if nothing has been saved.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        match read_to_string(PATH) {
+            Ok(text) => Ok(toml::from_str(&text)?),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(write(PATH, toml::to_string(self)?)?)
+    }
+}
+
+/// What's picked in the nets, and the best score in each drill.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Nets {
+    pub setup: Setup,
+    pub session: Option<Session>,
+    pub records: Records,
+}
+
+/// Filled and empty stars out of three.
+pub fn stars(count: usize) -> String {
+    format!("{}{}", "*".repeat(count), "-".repeat(3 - count))
+}
+
+impl Game<'_> {
+    /// Scores the ball just bowled in the nets, saving the drill's score and going back to the nets
+    /// once it's done. Returns whether it was.
+    pub fn score_nets_ball(&mut self, teams: &Teams, carry: Vec3) -> bool {
+        let Some(session) = &mut self.nets.session else {
+            return false;
+        };
+        let batting = &teams.batting;
+        let done = session.bowled(
+            batting.runs - batting.extras,
+            batting.wickets,
+            carry,
+            &mut rand::thread_rng(),
+        );
+        let Some((drill, score)) = done else {
+            return false;
+        };
+        let records = &mut self.nets.records;
+        records.record(drill, score);
+        if let Err(error) = records.save() {
+            eprintln!("couldn't save the nets records: {error}");
+        }
+        self.nets.session = None;
+        self.state = State::Nets {
+            result: Some((drill, score)),
+        };
+        true
+    }
+
+    /// Picking a delivery to practise against or a drill, with the best star rating in each.
+    pub fn draw_nets(&mut self) {
+        const HEADING_TOP: f32 = 4.;
+        const GAP: f32 = 3.;
+        const COLUMN_GAP: f32 = 8.;
+        const SMALL_TEXT_SIZE: u16 = 3;
+        let State::Nets { result } = self.state else {
+            unreachable!()
+        };
+        let setup = self.nets.setup;
+        let button = |text: String, id| {
+            Node::text(text, SMALL_TEXT_SIZE)
+                .padding(Self::BUTTON_PADDING)
+                .button(id)
+        };
+        let practice = Node::column(
+            Self::LINE_GAP,
+            [
+                Node::text("Practice", Self::TEXT_SIZE),
+                button(format!("Type: {}", setup.movement), 0),
+                button(format!("Speed: {}", setup.speed), 1),
+                button(format!("Line: {}", setup.line), 2),
+                button(format!("Length: {}", setup.length), 3),
+                Node::text("Bowl", Self::TEXT_SIZE)
+                    .padding(Self::BUTTON_PADDING)
+                    .button(4),
+            ],
+        );
+        let drills = Node::column(
+            Self::LINE_GAP,
+            iter::once(Node::text("Drills", Self::TEXT_SIZE)).chain(
+                Drill::VARIANTS.iter().zip(5..).map(|(&drill, id)| {
+                    let best = self.nets.records.best.get(&drill);
+                    let count = best.map_or(0, |&best| drill.stars(best));
+                    button(format!("{drill} {}", stars(count)), id)
+                }),
+            ),
+        );
+        let status = match result {
+            Some((drill, score)) => format!("{drill}: {score} {}", stars(drill.stars(score))),
+            None => "Pick a delivery or a drill".to_string(),
+        };
+        let root = Node::column(
+            GAP,
+            [
+                Node::text("Nets", Self::HEADING_TEXT_SIZE),
+                Node::row(COLUMN_GAP, [practice, drills]),
+                Node::text(status, Self::TEXT_SIZE),
+            ],
+        );
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Top, vec2(0., HEADING_TOP));
+        });
+        let setup = &mut self.nets.setup;
+        match activated {
+            Some(0) => setup.movement = cycle(setup.movement),
+            Some(1) => setup.speed = cycle(setup.speed),
+            Some(2) => setup.line = cycle(setup.line),
+            Some(3) => setup.length = cycle(setup.length),
+            Some(4) => {
+                let setup = *setup;
+                self.start_nets(Session::practice(setup));
+            }
+            Some(id) => {
+                let drill = Drill::VARIANTS[id - 5];
+                self.start_nets(Session::drill(drill, &mut rand::thread_rng()));
+            }
+            None => {}
+        }
+    }
+
+    /// Goes into the nets, where deliveries keep coming without a match being played.
+    fn start_nets(&mut self, session: Session) {
+        let overs = tuning::current().overs;
+        self.competition = None;
+        self.nets.session = Some(session);
+        let teams = Teams::new([TEAM_NAMES[PLAYER], "Nets"], overs);
+        self.state = Self::init_playing_state(teams, &self.assets);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_keep_the_best_score_and_round_trip() {
+        let mut records = Records::default();
+        records.record(Drill::Yorkers, 4);
+        records.record(Drill::Yorkers, 2);
+        records.record(Drill::ShortBall, 9);
+        assert_eq!(records.best.get(&Drill::Yorkers), Some(&4));
+        assert_eq!(records.best.get(&Drill::CoverDrives), None);
+        let text = toml::to_string(&records).unwrap();
+        assert_eq!(toml::from_str::<Records>(&text).unwrap(), records);
+    }
+
+    #[test]
+    fn drills_score_each_ball_from_the_totals() {
+        let mut rng = rand::thread_rng();
+        let mut session = Session::drill(Drill::ShortBall, &mut rng);
+        assert_eq!(session.bowled(4, 0, Vec3::ZERO, &mut rng), None);
+        assert_eq!(session.bowled(4, 0, Vec3::ZERO, &mut rng), None);
+        // Nothing for a ball the batter was out on.
+        assert_eq!(session.bowled(6, 1, Vec3::ZERO, &mut rng), None);
+        assert_eq!(session.score, 4);
+        for _ in 0..2 {
+            session.bowled(6, 1, Vec3::ZERO, &mut rng);
+        }
+        assert_eq!(
+            session.bowled(7, 1, Vec3::ZERO, &mut rng),
+            Some((Drill::ShortBall, 5))
+        );
+    }
+}
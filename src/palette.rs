@@ -0,0 +1,129 @@
+use crate::Colour;
+use macroquad::color::Color;
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::{read_dir, read_to_string},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        LazyLock,
+    },
+};
+
+/// How many colours every palette has, one for each variant of `Colour`.
+pub const LEN: usize = Colour::ALL.len();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub name: String,
+    pub colours: [Color; LEN],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(retro_wicket_palette::Error),
+    Count(usize),
+    Extension(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => error.fmt(f),
+            Self::Count(count) => write!(f, "expected {LEN} colours, found {count}"),
+            Self::Extension(extension) => write!(f, "unknown palette extension {extension:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<retro_wicket_palette::Error> for Error {
+    fn from(error: retro_wicket_palette::Error) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl Palette {
+    fn new(name: String, palette: retro_wicket_palette::Palette) -> Result<Self, Error> {
+        let colours = palette
+            .entries
+            .iter()
+            .map(|entry| {
+                let [red, green, blue] = entry.rgb;
+                Color::from_rgba(red, green, blue, u8::MAX)
+            })
+            .collect::<Vec<_>>();
+        Ok(Self {
+            name: palette.name.unwrap_or(name),
+            colours: colours
+                .try_into()
+                .map_err(|colours: Vec<_>| Error::Count(colours.len()))?,
+        })
+    }
+
+    /// Parses a palette with one `rrggbb` colour per line, as exported by Lospec.
+    pub fn parse_hex(name: impl Into<String>, source: &str) -> Result<Self, Error> {
+        Self::new(name.into(), retro_wicket_palette::parse_hex(source)?)
+    }
+
+    /// Parses a GIMP palette, taking the name from its `Name:` header if it has one.
+    pub fn parse_gpl(fallback_name: impl Into<String>, source: &str) -> Result<Self, Error> {
+        Self::new(
+            fallback_name.into(),
+            retro_wicket_palette::parse_gpl(source)?,
+        )
+    }
+
+    /// Parses a `.hex` or `.gpl` file, using the file name as the name if the file has none.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = read_to_string(path)?;
+        let name = path
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy());
+        Ok(match extension.as_deref() {
+            Some("hex") => Self::parse_hex(name, &source)?,
+            Some("gpl") => Self::parse_gpl(name, &source)?,
+            extension => Err(Error::Extension(extension.unwrap_or_default().to_string()))?,
+        })
+    }
+}
+
+/// The palettes built into the game, followed by any found in a `palettes` directory next to
+/// where the game is run from. The first is the default, which `Colour` is generated from.
+pub static THEMES: LazyLock<Vec<Palette>> = LazyLock::new(|| {
+    let mut themes = vec![
+        Palette {
+            name: "Endesga 16".to_string(),
+            colours: Colour::ALL.map(Colour::colour),
+        },
+        Palette::parse_gpl("", include_str!("palettes/colour-blind.gpl")).unwrap(),
+        Palette::parse_hex("High contrast", include_str!("palettes/high-contrast.hex")).unwrap(),
+    ];
+    if let Ok(entries) = read_dir("palettes") {
+        for entry in entries.flatten() {
+            match Palette::load(&entry.path()) {
+                Ok(palette) => themes.push(palette),
+                Err(error) => eprintln!("skipping {}: {error}", entry.path().display()),
+            }
+        }
+    }
+    themes
+});
+
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+pub fn active() -> &'static Palette {
+    &THEMES[active_index()]
+}
+
+pub fn active_index() -> usize {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+pub fn set_active(index: usize) {
+    ACTIVE.store(index % THEMES.len(), Ordering::Relaxed);
+}
@@ -0,0 +1,1046 @@
+use crate::{
+    debug,
+    shot::{Contact, Shot},
+    trajectory,
+    tuning::{self, Tuning, UmpireTuning},
+    AnimatedSprite, Colour, Fielder, Game, SpriteState, State,
+};
+use macroquad::{
+    camera::set_default_camera,
+    input::{is_key_pressed, set_cursor_grab, show_mouse, KeyCode},
+    math::{vec2, vec3, Vec2, Vec3},
+    models::draw_sphere_wires,
+    shapes::draw_line,
+    time::{get_frame_time, get_time},
+};
+use nalgebra::Vector3;
+use rand::random;
+use std::{
+    fmt::{self, Display, Formatter},
+    iter,
+    mem::take,
+};
+use strum::Display;
+
+/// How many unsuccessful reviews each side gets per innings.
+pub const REVIEWS_PER_INNINGS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Dismissal {
+    Bowled,
+    Caught,
+    #[strum(serialize = "lbw")]
+    Lbw,
+    #[strum(serialize = "run out")]
+    RunOut,
+}
+
+/// A set of stumps, as the region the ball has to pass through to hit them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stumps {
+    pub z: f32,
+    /// How far either side of middle stump the ball's centre can pass and still clip a stump.
+    pub half_width: f32,
+    pub height: f32,
+}
+
+impl Stumps {
+    pub fn hit(&self, path: &[Vec3]) -> bool {
+        trajectory::crossing(path, self.z)
+            .is_some_and(|(_, point)| point.x.abs() <= self.half_width && point.y <= self.height)
+    }
+
+    /// The stumps shrunk by `margin` along their sides and top.
+    pub fn inset(self, margin: f32) -> Self {
+        Self {
+            half_width: self.half_width - margin,
+            height: self.height - margin,
+            ..self
+        }
+    }
+}
+
+/// An appeal for a dismissal and the on-field umpire's answer to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Appeal {
+    pub dismissal: Dismissal,
+    /// The replay frame the decision hinges on: the impact, the catch or the bails coming off.
+    pub frame: usize,
+    /// Whether the batter really is out, which only comes to light if the decision is reviewed.
+    pub out: bool,
+    /// Whether the umpire gave them out.
+    pub given: bool,
+}
+
+impl Appeal {
+    /// The umpire gets the decision wrong with the given chance.
+    pub fn new(dismissal: Dismissal, frame: usize, out: bool, error_chance: f32) -> Self {
+        Self {
+            dismissal,
+            frame,
+            out,
+            given: out != (random::<f32>() < error_chance),
+        }
+    }
+
+    /// A throw broke the wicket with the batter `short` of their ground, negative if they made
+    /// it. The umpire can only get a close one wrong.
+    pub fn run_out(frame: usize, short: f32, umpire: &UmpireTuning) -> Self {
+        let error_chance = if short.abs() < umpire.close_run_out {
+            umpire.error_chance
+        } else {
+            0.
+        };
+        Self::new(Dismissal::RunOut, frame, short > 0., error_chance)
+    }
+
+    /// Being bowled is plain to see, so it can't be reviewed.
+    pub fn reviewable(self) -> bool {
+        self.dismissal != Dismissal::Bowled
+    }
+}
+
+impl Display for Appeal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.given {
+            write!(f, "Out - {}", self.dismissal)
+        } else {
+            write!(f, "Not out")
+        }
+    }
+}
+
+/// Everything on the field during one frame of a delivery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub time: f32,
+    pub ball: Vec3,
+    pub batter: Vec3,
+    /// How far the batter has run towards the other end.
+    pub run: f32,
+    pub fielders: Vec<Vec3>,
+}
+
+/// Where a ball that hit the batter's pads would have gone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tracking {
+    pub pitch: Option<Vec3>,
+    pub impact: Vec3,
+    /// The ball's predicted path on from the impact, as if the batter weren't there.
+    pub path: Vec<Vec3>,
+    /// Whether the batter played a shot, without which they can be out to a ball hitting them
+    /// outside the line of off stump.
+    pub offered_shot: bool,
+}
+
+impl Tracking {
+    /// Leg side is towards negative x, as for the bat direction.
+    pub fn pitched_outside_leg(&self, stumps: Stumps) -> bool {
+        self.pitch.is_some_and(|pitch| pitch.x < -stumps.half_width)
+    }
+
+    pub fn impact_in_line(&self, stumps: Stumps) -> bool {
+        self.impact.x.abs() <= stumps.half_width
+    }
+
+    pub fn hitting(&self, stumps: Stumps) -> bool {
+        stumps.hit(&self.path)
+    }
+
+    /// Off side is towards positive x.
+    pub fn impact_outside_off(&self, stumps: Stumps) -> bool {
+        self.impact.x > stumps.half_width
+    }
+
+    /// Not playing a shot only lets an impact outside off stump be out. One outside leg never is.
+    pub fn out(&self, stumps: Stumps) -> bool {
+        !self.pitched_outside_leg(stumps)
+            && (self.impact_in_line(stumps)
+                || (!self.offered_shot && self.impact_outside_off(stumps)))
+            && self.hitting(stumps)
+    }
+
+    /// Whether the ball would only have clipped the stumps, passing less than `margin` inside
+    /// their edges.
+    pub fn clipping(&self, stumps: Stumps, margin: f32) -> bool {
+        self.hitting(stumps) && !self.hitting(stumps.inset(margin))
+    }
+
+    /// Where the ball pitched and hit the pad, and whether it would have gone on to hit the stumps.
+    fn evidence(&self, stumps: Stumps, margin: f32) -> [String; 3] {
+        let pitching = if self.pitched_outside_leg(stumps) {
+            "outside leg"
+        } else {
+            "in line"
+        };
+        let impact = if self.impact_in_line(stumps) {
+            "in line"
+        } else if self.impact_outside_off(stumps) {
+            "outside off"
+        } else {
+            "outside leg"
+        };
+        let wickets = if self.clipping(stumps, margin) {
+            "umpire's call"
+        } else if self.hitting(stumps) {
+            "hitting"
+        } else {
+            "missing"
+        };
+        [
+            format!("Pitching: {pitching}"),
+            format!("Impact: {impact}"),
+            format!("Wickets: {wickets}"),
+        ]
+    }
+}
+
+/// The frame at which the bat met the ball.
+pub fn contact_frame(frames: &[Frame], contact: Contact) -> Option<usize> {
+    frames.iter().position(|frame| frame.time >= contact.time)
+}
+
+/// A recorded delivery and the appeal made on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub appeal: Appeal,
+    pub frames: Vec<Frame>,
+    pub contact: Option<Contact>,
+    pub tracking: Option<Tracking>,
+}
+
+impl Review {
+    /// The frame at which the bat met the ball, for edge detection.
+    pub fn contact_frame(&self) -> Option<usize> {
+        contact_frame(&self.frames, self.contact?)
+    }
+
+    /// Whether the ball has bounced since it left the bat, as of `frame`.
+    pub fn bounced(&self, frame: usize) -> bool {
+        let Some(contact) = self.contact_frame().filter(|&contact| contact <= frame) else {
+            return false;
+        };
+        let path = self.frames[contact..=frame]
+            .iter()
+            .map(|frame| frame.ball)
+            .collect::<Vec<_>>();
+        trajectory::bounce(&path).is_some()
+    }
+
+    /// Whether an lbw that's otherwise out would only have clipped the stumps by less than
+    /// `margin`, which leaves the decision with the on-field umpire.
+    pub fn umpires_call(&self, stumps: Stumps, margin: f32) -> bool {
+        self.appeal.dismissal == Dismissal::Lbw
+            && self
+                .tracking
+                .as_ref()
+                .is_some_and(|tracking| tracking.out(stumps) && tracking.clipping(stumps, margin))
+    }
+
+    /// Whether the batter is out once the review is done. On the umpire's call the decision
+    /// on the field stands.
+    pub fn out(&self, stumps: Stumps, margin: f32) -> bool {
+        if self.umpires_call(stumps, margin) {
+            self.appeal.given
+        } else {
+            self.appeal.out
+        }
+    }
+
+    /// What the third umpire can see as of `frame`, a line each, ending with which frame it is.
+    pub fn evidence(
+        &self,
+        frame: usize,
+        stumps: Stumps,
+        margin: f32,
+        run_length: f32,
+    ) -> Vec<String> {
+        let appeal = self.appeal;
+        let reached = frame >= appeal.frame;
+        let mut lines = vec![format!("On field: {appeal}")];
+        if reached || self.contact_frame().is_some_and(|contact| frame >= contact) {
+            lines.push(match self.contact {
+                Some(contact) if contact.edged => "Edge detected".to_string(),
+                Some(_) => "Bat on ball".to_string(),
+                None => "No edge".to_string(),
+            });
+        }
+        match appeal.dismissal {
+            Dismissal::Lbw => {
+                if let Some(tracking) = self.tracking.as_ref().filter(|_| reached) {
+                    lines.extend(tracking.evidence(stumps, margin));
+                }
+            }
+            Dismissal::Caught => {
+                let bounced = if self.bounced(frame) {
+                    "bounced"
+                } else {
+                    "in the air"
+                };
+                lines.push(format!("Ball {bounced}"));
+            }
+            Dismissal::RunOut => {
+                let short = run_length - self.frames[frame].run;
+                lines.push(if short > 0. {
+                    format!("Batter short by {short:.2}m")
+                } else {
+                    "Batter in".to_string()
+                });
+                lines.push(format!("Bails {}", if reached { "off" } else { "on" }));
+            }
+            Dismissal::Bowled => {}
+        }
+        lines.push(format!("Frame {}/{}", frame + 1, self.frames.len()));
+        lines
+    }
+}
+
+impl Game<'_> {
+    /// Seconds a side has to decide whether to review a decision.
+    pub const REVIEW_WINDOW: f32 = 5.;
+    /// Seconds of the delivery shown before the moment a review hinges on.
+    const REPLAY_LEAD: f32 = 2.;
+
+    const TRACE_COLOUR: Colour = Colour::Glow;
+    const OUT_COLOUR: Colour = Colour::Blood;
+    const NOT_OUT_COLOUR: Colour = Colour::Flora;
+
+    /// Where a throw has to reach for whoever is at the bowler's end to gather it and break the
+    /// wicket.
+    const BOWLING_END_WICKET: Stumps = Stumps {
+        z: -Self::BETWEEN_WICKETS / 2.,
+        half_width: 0.5,
+        height: 1.5,
+    };
+    /// The batter's pads, across the pitch from the middle stump, which the batter stands just
+    /// leg side of.
+    const PAD_LEG_EDGE: f32 = -0.4;
+    const PAD_OFF_EDGE: f32 = 0.;
+    const PAD_HEIGHT: f32 = 0.6;
+    /// How much of its speed the ball keeps when it bounces off the pads.
+    const PAD_REBOUND: f32 = 0.2;
+    const HAND_HEIGHT: Vec3 = vec3(0., 1., 0.);
+    /// Seconds a catch can be taken after the ball bounces and still be appealed for.
+    const BUMP_BALL_TIME: f32 = 0.3;
+    /// Seconds after release until the ball is dead if it isn't hit, and at most if it is.
+    const UNHIT_BALL_TIME: f32 = 3.;
+    const HIT_BALL_TIME: f32 = 15.;
+    /// How far ahead a ball that hits the pads is tracked.
+    const TRACKING_TIME: f32 = 1.;
+
+    /// Hands the appeal on the delivery just played to the third umpire, starting the replay a
+    /// little before the moment it hinges on.
+    pub fn start_review(&mut self, appeal: Appeal) {
+        let State::Playing {
+            teams,
+            frames,
+            contact,
+            tracking,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let time = get_time() as f32;
+        let frame = frames
+            .iter()
+            .position(|frame| frame.time >= frames[appeal.frame].time - Self::REPLAY_LEAD)
+            .unwrap_or_default();
+        self.state = State::Reviewing {
+            teams: teams.clone(),
+            review: Review {
+                appeal,
+                frames: take(frames),
+                contact: *contact,
+                tracking: tracking.take(),
+            },
+            frame,
+            stepping: false,
+            decided_at: None,
+            batter: AnimatedSprite::new(self.assets.batter.clone(), Self::BATTER_SIZE, time),
+            fielder: AnimatedSprite::new(self.assets.fielder.clone(), Self::BATTER_SIZE, time),
+        };
+    }
+
+    /// Shows the umpire's decision on an appeal, and whether it can be reviewed.
+    pub fn draw_appeal(&mut self) {
+        let State::Playing {
+            appeal: Some(appeal),
+            ref mut teams,
+            ..
+        } = self.state
+        else {
+            return;
+        };
+        let reviews = appeal
+            .reviewable()
+            .then(|| teams.reviewing(appeal))
+            .map(|team| (team.name, team.reviews))
+            .filter(|&(_, reviews)| reviews > 0);
+        let colour = if appeal.given {
+            Self::OUT_COLOUR
+        } else {
+            Self::NOT_OUT_COLOUR
+        };
+        self.draw_banner(
+            &appeal.to_string(),
+            Self::BANNER_TOP + 2. * Self::BANNER_GAP,
+            colour.themed(),
+        );
+        if let Some((name, reviews)) = reviews {
+            self.draw_caption(
+                &format!("{name}: R to review ({reviews} left)"),
+                Self::BANNER_TOP + 3. * Self::BANNER_GAP,
+                Self::CAPTION_COLOUR.themed(),
+            );
+        }
+    }
+
+    /// Replays the delivery up to the moment the appeal hinges on, lets the frames around it be
+    /// stepped through, and once a decision is made shows it before moving on.
+    pub fn draw_reviewing(&mut self) {
+        const VERDICT_TIME: f32 = 3.;
+        show_mouse(true);
+        set_cursor_grab(false);
+        let State::Reviewing {
+            teams,
+            review,
+            frame,
+            stepping,
+            decided_at,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let time = get_time() as f32;
+        let appeal = review.appeal;
+        let last = review.frames.len() - 1;
+        if !self.console.open && decided_at.is_none() {
+            if is_key_pressed(KeyCode::Left) {
+                *stepping = true;
+                *frame = frame.saturating_sub(1);
+            }
+            if is_key_pressed(KeyCode::Right) {
+                *stepping = true;
+                *frame = (*frame + 1).min(last);
+            }
+            if is_key_pressed(KeyCode::Enter) {
+                *decided_at = Some(time);
+                // Like a successful one, a review decided on the umpire's call isn't lost.
+                let stumps = Self::BATTING_STUMPS;
+                if review.out(stumps, Self::BALL_RADIUS) == appeal.given
+                    && !review.umpires_call(stumps, Self::BALL_RADIUS)
+                {
+                    teams.reviewing(appeal).reviews -= 1;
+                }
+            }
+        }
+        if !*stepping && *frame < appeal.frame {
+            let replay_time = review.frames[*frame].time + get_frame_time();
+            while *frame < appeal.frame && review.frames[*frame + 1].time <= replay_time {
+                *frame += 1;
+            }
+        }
+        if let Some(decided_at) = *decided_at {
+            if time - decided_at > VERDICT_TIME {
+                if review.out(Self::BATTING_STUMPS, Self::BALL_RADIUS) {
+                    teams.batting.wickets += 1;
+                }
+                let carry = Self::carry(&review.frames);
+                let teams = teams.clone();
+                self.next_delivery(teams, carry);
+                return;
+            }
+        }
+
+        self.draw_replay_to_render_texture();
+        set_default_camera();
+        self.post_process.draw(
+            &self.render_target.texture,
+            Self::transform_point(Vec2::ZERO),
+            Self::transform_size(Self::SIZE),
+        );
+        self.draw_review_evidence();
+    }
+
+    /// The replay frame on screen, from wherever shows the kind of dismissal best, with ball
+    /// tracking once it reaches the impact.
+    fn draw_replay_to_render_texture(&self) {
+        let State::Reviewing {
+            ref review,
+            frame,
+            ref batter,
+            ref fielder,
+            ..
+        } = self.state
+        else {
+            unreachable!()
+        };
+        let time = get_time() as f32;
+        let tuning = tuning::current();
+        let (camera_position, camera_target) = match review.appeal.dismissal {
+            Dismissal::Lbw | Dismissal::Bowled => (
+                vec3(0., 2.5, -Self::BETWEEN_WICKETS / 2. - 6.),
+                vec3(0., 0.5, Self::BETWEEN_WICKETS / 2.),
+            ),
+            Dismissal::Caught => (tuning.camera_position, tuning.camera_target),
+            Dismissal::RunOut => (
+                vec3(10., 2., Self::BOWLING_POPPING_CREASE),
+                vec3(0., 0.5, Self::BOWLING_POPPING_CREASE - 0.6),
+            ),
+        };
+        Self::draw_field(&self.render_target, camera_position, camera_target);
+        let shown = &review.frames[frame];
+        batter.draw(shown.batter, camera_position, time);
+        for &position in &shown.fielders {
+            fielder.draw(position, camera_position, time);
+        }
+        Self::draw_ball(shown.ball);
+        let reached = frame >= review.appeal.frame;
+        if let Some(tracking) = review.tracking.as_ref().filter(|_| reached) {
+            debug::draw_path(&tracking.path, Self::AID_COLOUR.themed());
+            draw_sphere_wires(
+                tracking.impact,
+                Self::BALL_RADIUS * 2.,
+                None,
+                Self::AID_COLOUR.themed(),
+            );
+            if let Some(pitch) = tracking.pitch {
+                Self::draw_marker(pitch, 0.3, Self::AID_COLOUR.themed());
+            }
+        }
+    }
+
+    /// The third umpire's view of the evidence at the frame on screen, an edge detection trace,
+    /// and the verdict once there is one.
+    fn draw_review_evidence(&mut self) {
+        const TRACE_TOP: f32 = 84.;
+        const LINE_HEIGHT: f32 = 7.;
+        let State::Reviewing {
+            ref review,
+            frame,
+            decided_at,
+            ..
+        } = self.state
+        else {
+            return;
+        };
+        Self::draw_edge_trace(review, frame, TRACE_TOP);
+        let appeal = review.appeal;
+        if decided_at.is_none() {
+            let lines = review.evidence(
+                frame,
+                Self::BATTING_STUMPS,
+                Self::BALL_RADIUS,
+                Self::RUN_LENGTH,
+            );
+            for (index, line) in lines.iter().enumerate() {
+                self.draw_caption(
+                    line,
+                    Self::BANNER_TOP + index as f32 * LINE_HEIGHT,
+                    Self::CAPTION_COLOUR.themed(),
+                );
+            }
+            self.draw_caption(
+                "Left/right: step, enter: decide",
+                TRACE_TOP + 2. * LINE_HEIGHT,
+                Self::CAPTION_COLOUR.themed(),
+            );
+            return;
+        }
+        let stumps = Self::BATTING_STUMPS;
+        let umpires_call = review.umpires_call(stumps, Self::BALL_RADIUS);
+        let (verdict, colour) = if review.out(stumps, Self::BALL_RADIUS) {
+            ("Out", Self::OUT_COLOUR)
+        } else {
+            ("Not out", Self::NOT_OUT_COLOUR)
+        };
+        self.draw_banner(verdict, Self::BANNER_TOP, colour.themed());
+        let outcome = if umpires_call {
+            "Umpire's call"
+        } else if appeal.out == appeal.given {
+            "Decision stands"
+        } else {
+            "Decision overturned"
+        };
+        self.draw_caption(
+            outcome,
+            Self::BANNER_TOP + Self::BANNER_GAP,
+            Self::CAPTION_COLOUR.themed(),
+        );
+    }
+
+    /// A flat line across the replay with a spike where the bat met the ball, smaller for an
+    /// edge, and a cursor at the frame on screen.
+    fn draw_edge_trace(review: &Review, frame: usize, top: f32) {
+        const MARGIN: f32 = 10.;
+        const EDGE_HEIGHT: f32 = 4.;
+        const BAT_HEIGHT: f32 = 10.;
+        let width = Self::SIZE.x - 2. * MARGIN;
+        let step = width / review.frames.len() as f32;
+        let spike = review.contact.map(|contact| {
+            let height = if contact.edged {
+                EDGE_HEIGHT
+            } else {
+                BAT_HEIGHT
+            };
+            (review.contact_frame().unwrap_or_default(), height)
+        });
+        let points = (0..review.frames.len()).map(|index| {
+            let height = spike
+                .filter(|&(contact, _)| contact == index)
+                .map_or(0., |(_, height)| height);
+            Self::transform_point(vec2(MARGIN + index as f32 * step, top - height))
+        });
+        let colour = Self::TRACE_COLOUR.themed();
+        let thickness = Self::transform_length(0.5);
+        for (start, end) in points.clone().zip(points.skip(1)) {
+            draw_line(start.x, start.y, end.x, end.y, thickness, colour);
+        }
+        let cursor = |y| Self::transform_point(vec2(MARGIN + frame as f32 * step, y));
+        let (cursor_top, cursor_bottom) = (cursor(top - BAT_HEIGHT), cursor(top + 2.));
+        draw_line(
+            cursor_top.x,
+            cursor_top.y,
+            cursor_bottom.x,
+            cursor_bottom.y,
+            thickness,
+            Self::CAPTION_COLOUR.themed(),
+        );
+    }
+
+    /// Records the frame for any review, then judges what the ball has done since the last one:
+    /// whether it's hit the stumps or the pads, been fielded, or the batter has been run out, and
+    /// whether it's dead.
+    pub fn adjudicate(&mut self, time: f32, frame_time: f32, tuning: &Tuning) {
+        let State::Playing {
+            start,
+            ball_thrown,
+            ball_hit,
+            call,
+            free_hit,
+            fielders,
+            run,
+            frames,
+            dead_at,
+            physics_stuff,
+            ball_rigidbody_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let ball = Vec3::from(*physics_stuff.bodies[*ball_rigidbody_handle].translation());
+        let live = *ball_thrown && dead_at.is_none();
+        if live {
+            frames.push(Frame {
+                time,
+                ball,
+                batter: Self::BATTER_POSITION + Vec3::NEG_Z * *run,
+                run: *run,
+                fielders: fielders.iter().map(|fielder| fielder.position).collect(),
+            });
+        }
+        // Only a run out can dismiss the batter off a no-ball, a wide or a free hit.
+        let outs_allowed = call.is_none() && !*free_hit;
+        let frame = frames.len().saturating_sub(1);
+        let previous = frames
+            .len()
+            .checked_sub(2)
+            .map_or(ball, |index| frames[index].ball);
+        let last_move = [previous, ball];
+        let elapsed = time - *start;
+        let ball_hit = *ball_hit;
+
+        if live {
+            self.judge_wicket(time, frame, last_move, outs_allowed, tuning);
+        }
+        if ball_hit {
+            self.field_ball(time, frame_time, frame, outs_allowed, tuning);
+        }
+        self.run_between_wickets(time, frame_time, frame, last_move, tuning);
+
+        let State::Playing { dead_at, .. } = &mut self.state else {
+            unreachable!()
+        };
+        let ball_time = if ball_hit {
+            Self::HIT_BALL_TIME
+        } else {
+            Self::UNHIT_BALL_TIME
+        };
+        if live && dead_at.is_none() && elapsed > tuning.ball_delay + ball_time {
+            *dead_at = Some(time);
+        }
+    }
+
+    /// Whether the ball's last move took it into the stumps, or into the pads when it hadn't been
+    /// hit, tracking where it would have gone on to for an lbw.
+    fn judge_wicket(
+        &mut self,
+        time: f32,
+        frame: usize,
+        last_move: [Vec3; 2],
+        outs_allowed: bool,
+        tuning: &Tuning,
+    ) {
+        let State::Playing {
+            shot,
+            ball_hit,
+            predicted_path,
+            frames,
+            tracking,
+            appeal,
+            dead_at,
+            physics_stuff,
+            ball_rigidbody_handle: ball_body_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        if Self::BATTING_STUMPS.hit(&last_move) {
+            if outs_allowed {
+                *appeal = Some(Appeal::new(Dismissal::Bowled, frame, true, 0.));
+            }
+            *dead_at = Some(time);
+        }
+
+        let pad = trajectory::crossing(&last_move, Self::BATTER_POSITION.z)
+            .map(|(_, point)| point)
+            .filter(|point| {
+                (Self::PAD_LEG_EDGE..=Self::PAD_OFF_EDGE).contains(&point.x)
+                    && point.y <= Self::PAD_HEIGHT
+            });
+        let Some(impact) = pad.filter(|_| !*ball_hit) else {
+            return;
+        };
+        let path = iter::once(impact)
+            .chain(physics_stuff.predict(*ball_body_handle, Self::TRACKING_TIME))
+            .collect();
+        let path_so_far = frames.iter().map(|frame| frame.ball).collect::<Vec<_>>();
+        let lbw = Tracking {
+            pitch: trajectory::bounce(&path_so_far).map(|(_, pitch)| pitch),
+            impact,
+            path,
+            offered_shot: shot.is_some_and(|shot| shot != Shot::Leave),
+        };
+        if outs_allowed {
+            let out = lbw.out(Self::BATTING_STUMPS);
+            *appeal = Some(Appeal::new(
+                Dismissal::Lbw,
+                frame,
+                out,
+                tuning.umpire.error_chance,
+            ));
+        }
+        *tracking = Some(lbw);
+        let ball_body = &mut physics_stuff.bodies[*ball_body_handle];
+        let incoming = Vec3::from(*ball_body.linvel());
+        let rebound = incoming * vec3(1., 1., -1.) * Self::PAD_REBOUND;
+        ball_body.set_linvel(Vector3::from(rebound), true);
+        *predicted_path = None;
+        *dead_at = Some(time);
+    }
+
+    /// Once the ball's been hit, scores a boundary, sets the batter running, and sends the
+    /// fielders after it until one of them catches it or throws it in.
+    fn field_ball(
+        &mut self,
+        time: f32,
+        frame_time: f32,
+        frame: usize,
+        outs_allowed: bool,
+        tuning: &Tuning,
+    ) {
+        let State::Playing {
+            teams,
+            shot,
+            contact,
+            predicted_path,
+            batter,
+            fielders,
+            thrown_in,
+            frames,
+            appeal,
+            dead_at,
+            physics_stuff,
+            ball_rigidbody_handle: ball_body_handle,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        let ball_body = &mut physics_stuff.bodies[*ball_body_handle];
+        let ball = Vec3::from(*ball_body.translation());
+        let ball_position = ball * vec3(1., 0., 1.);
+        let contact_frame = contact.and_then(|contact| contact_frame(frames, contact));
+        let since_contact = contact_frame.map_or(&[][..], |contact| &frames[contact..]);
+        let path_since_contact = since_contact
+            .iter()
+            .map(|frame| frame.ball)
+            .collect::<Vec<_>>();
+        let bounce =
+            trajectory::bounce(&path_since_contact).map(|(index, _)| since_contact[index].time);
+        if ball_position.length() > Self::BOUNDARY_DISTANCE {
+            batter.set_state(SpriteState::Celebrating, time);
+            if dead_at.is_none() {
+                teams.batting.runs += if bounce.is_some() { 4 } else { 6 };
+                *dead_at = Some(time);
+            }
+        } else if dead_at.is_none()
+            && *shot != Some(Shot::Block)
+            && batter.state == SpriteState::from(shot.unwrap_or(Shot::Leave))
+            && batter.is_finished(time)
+        {
+            batter.set_state(SpriteState::Running, time);
+        }
+        let reached = (!*thrown_in)
+            .then(|| Self::chase_ball(fielders, ball_position, frame_time, time))
+            .flatten();
+        let fielder = reached.filter(|_| dead_at.is_none() && ball.y < tuning.catch_height);
+        let Some(fielder) = fielder else {
+            return;
+        };
+        let hand = fielders[fielder].position + Self::HAND_HEIGHT;
+        let running = batter.state == SpriteState::Running;
+        // A catch that bounced just before it was taken still gets appealed.
+        let bump_ball = bounce.is_some_and(|bounce| time - bounce < Self::BUMP_BALL_TIME);
+        if outs_allowed && (bounce.is_none() || bump_ball) {
+            let error_chance = if contact.is_some_and(|contact| contact.edged) {
+                tuning.umpire.edge_error_chance
+            } else {
+                tuning.umpire.error_chance
+            };
+            *appeal = Some(Appeal::new(
+                Dismissal::Caught,
+                frame,
+                bounce.is_none(),
+                error_chance,
+            ));
+            *dead_at = Some(time);
+        } else if running {
+            // Thrown to land on the stumps at the end the batter is running to.
+            let target = vec3(0., Self::STUMP_HEIGHT / 2., -Self::BETWEEN_WICKETS / 2.);
+            let flight_time = hand.distance(target) / tuning.throw_speed;
+            let velocity = (target - hand) / flight_time
+                + Vec3::Y * -physics_stuff.gravity.y * flight_time / 2.;
+            ball_body.set_translation(hand.into(), true);
+            ball_body.set_linvel(Vector3::from(velocity), true);
+            *predicted_path = None;
+            *thrown_in = true;
+        } else {
+            *dead_at = Some(time);
+        }
+        if dead_at.is_some() {
+            ball_body.set_translation(hand.into(), true);
+            ball_body.set_enabled(false);
+            *predicted_path = None;
+        }
+    }
+
+    /// Moves a running batter along the pitch, completing the run when they make their ground,
+    /// or appealing for a run out if a throw breaks the wicket first.
+    fn run_between_wickets(
+        &mut self,
+        time: f32,
+        frame_time: f32,
+        frame: usize,
+        last_move: [Vec3; 2],
+        tuning: &Tuning,
+    ) {
+        let State::Playing {
+            teams,
+            batter,
+            run,
+            thrown_in,
+            appeal,
+            dead_at,
+            ..
+        } = &mut self.state
+        else {
+            unreachable!()
+        };
+        if batter.state == SpriteState::Running && dead_at.is_none() {
+            *run = tuning.running_speed.mul_add(frame_time, *run);
+            if *run >= Self::RUN_LENGTH {
+                *run = Self::RUN_LENGTH;
+                teams.batting.runs += 1;
+                batter.set_state(SpriteState::Stance, time);
+                *dead_at = Some(time);
+            }
+        }
+
+        if *thrown_in && dead_at.is_none() && Self::BOWLING_END_WICKET.hit(&last_move) {
+            *appeal = Some(Appeal::run_out(
+                frame,
+                Self::RUN_LENGTH - *run,
+                &tuning.umpire,
+            ));
+            batter.set_state(SpriteState::Stance, time);
+            *dead_at = Some(time);
+        }
+    }
+
+    /// Sends the nearest fielder after the ball, returning their index once they reach it.
+    fn chase_ball(
+        fielders: &mut [Fielder],
+        ball_position: Vec3,
+        delta_time: f32,
+        time: f32,
+    ) -> Option<usize> {
+        let tuning = tuning::current();
+        let chaser = fielders
+            .iter()
+            .map(|fielder| fielder.position.distance(ball_position))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index);
+        for (index, fielder) in fielders.iter_mut().enumerate() {
+            let to_ball = ball_position - fielder.position;
+            if Some(index) == chaser && to_ball.length() > tuning.fielder_reach {
+                fielder.position += to_ball.clamp_length_max(tuning.fielder_speed * delta_time);
+                fielder.sprite.set_state(SpriteState::Running, time);
+            } else {
+                fielder.sprite.set_state(SpriteState::Stance, time);
+            }
+        }
+        chaser.filter(|&chaser| {
+            fielders[chaser].position.distance(ball_position) <= tuning.fielder_reach
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STUMPS: Stumps = Stumps {
+        z: 10.,
+        half_width: 0.15,
+        height: 0.75,
+    };
+    const MARGIN: f32 = 0.04;
+
+    /// A ball hitting the pad in front of the stumps at `impact` and going on straight to cross
+    /// them at `stumps`.
+    fn tracking(pitch: f32, impact: f32, stumps: Vec3, offered_shot: bool) -> Tracking {
+        let impact = vec3(impact, 0.4, STUMPS.z - 1.);
+        Tracking {
+            pitch: Some(vec3(pitch, 0., STUMPS.z - 4.)),
+            impact,
+            path: vec![impact, stumps, stumps + Vec3::Z * 0.5],
+            offered_shot,
+        }
+    }
+
+    fn at_stumps(x: f32, y: f32) -> Vec3 {
+        vec3(x, y, STUMPS.z)
+    }
+
+    fn review(tracking: Tracking, given: bool) -> Review {
+        let out = tracking.out(STUMPS);
+        Review {
+            appeal: Appeal {
+                dismissal: Dismissal::Lbw,
+                frame: 0,
+                out,
+                given,
+            },
+            frames: Vec::new(),
+            contact: None,
+            tracking: Some(tracking),
+        }
+    }
+
+    #[test]
+    fn stumps_are_hit_within_their_width_and_height() {
+        let path = |end: Vec3| [vec3(end.x, end.y, 0.), end + Vec3::Z];
+        assert!(STUMPS.hit(&path(at_stumps(0., 0.5))));
+        assert!(STUMPS.hit(&path(at_stumps(-0.15, 0.75))));
+        assert!(!STUMPS.hit(&path(at_stumps(0.2, 0.5))));
+        assert!(!STUMPS.hit(&path(at_stumps(0., 0.8))));
+        assert!(!STUMPS.hit(&[vec3(0., 0.5, 0.), vec3(0., 0.5, STUMPS.z - 1.)]));
+    }
+
+    #[test]
+    fn in_line_and_hitting_is_out() {
+        let straight = tracking(0., 0., at_stumps(0., 0.5), false);
+        assert!(straight.out(STUMPS));
+        assert!(tracking(0., 0., at_stumps(0., 0.5), true).out(STUMPS));
+    }
+
+    #[test]
+    fn pitched_outside_leg_is_never_out() {
+        let outside_leg = tracking(-0.3, 0., at_stumps(0., 0.5), false);
+        assert!(outside_leg.pitched_outside_leg(STUMPS));
+        assert!(outside_leg.hitting(STUMPS));
+        assert!(!outside_leg.out(STUMPS));
+    }
+
+    #[test]
+    fn impact_outside_off_is_only_out_without_a_shot() {
+        let playing = tracking(0.3, 0.3, at_stumps(0.1, 0.5), true);
+        assert!(playing.impact_outside_off(STUMPS));
+        assert!(!playing.out(STUMPS));
+        let leaving = tracking(0.3, 0.3, at_stumps(0.1, 0.5), false);
+        assert!(leaving.out(STUMPS));
+    }
+
+    #[test]
+    fn impact_outside_leg_is_never_out() {
+        assert!(!tracking(0., -0.3, at_stumps(0., 0.5), false).out(STUMPS));
+    }
+
+    #[test]
+    fn missing_the_stumps_is_not_out() {
+        let over = tracking(0., 0., at_stumps(0., 1.), false);
+        assert!(!over.hitting(STUMPS));
+        assert!(!over.out(STUMPS));
+        assert!(!tracking(0., 0.1, at_stumps(0.3, 0.5), false).out(STUMPS));
+    }
+
+    #[test]
+    fn clipping_the_stumps_is_the_umpires_call() {
+        let clipping = tracking(0., 0.1, at_stumps(0.13, 0.5), false);
+        assert!(clipping.out(STUMPS));
+        assert!(clipping.clipping(STUMPS, MARGIN));
+        let given = review(clipping.clone(), true);
+        assert!(given.umpires_call(STUMPS, MARGIN));
+        assert!(given.out(STUMPS, MARGIN));
+        let not_given = review(clipping, false);
+        assert!(not_given.umpires_call(STUMPS, MARGIN));
+        assert!(!not_given.out(STUMPS, MARGIN));
+
+        let hitting = review(tracking(0., 0., at_stumps(0., 0.5), false), false);
+        assert!(!hitting.umpires_call(STUMPS, MARGIN));
+        assert!(hitting.out(STUMPS, MARGIN));
+        // Clipping the stumps doesn't matter if the impact was outside the line.
+        let outside_leg = review(tracking(0., -0.3, at_stumps(0.13, 0.5), false), true);
+        assert!(!outside_leg.umpires_call(STUMPS, MARGIN));
+        assert!(!outside_leg.out(STUMPS, MARGIN));
+    }
+
+    #[test]
+    fn run_out_when_short_of_the_crease() {
+        let mut umpire = crate::Tuning::parse("").unwrap().umpire;
+        umpire.close_run_out = 0.5;
+        umpire.error_chance = 0.;
+        let short = Appeal::run_out(3, 0.2, &umpire);
+        assert_eq!(short.dismissal, Dismissal::RunOut);
+        assert_eq!(short.frame, 3);
+        assert!(short.out && short.given);
+        let made_it = Appeal::run_out(3, -0.2, &umpire);
+        assert!(!made_it.out && !made_it.given);
+
+        // An umpire who always gets close ones wrong still gets clear ones right.
+        umpire.error_chance = 1.;
+        let close = Appeal::run_out(3, 0.2, &umpire);
+        assert!(close.out && !close.given);
+        let clear = Appeal::run_out(3, 2., &umpire);
+        assert!(clear.out && clear.given);
+        let clearly_in = Appeal::run_out(3, -2., &umpire);
+        assert!(!clearly_in.out && !clearly_in.given);
+    }
+}
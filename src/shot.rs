@@ -0,0 +1,131 @@
+use crate::tuning::Tuning;
+use macroquad::input::{is_key_down, is_key_pressed, is_mouse_button_down, KeyCode, MouseButton};
+use rand::random;
+use strum::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Shot {
+    Block,
+    Drive,
+    Cut,
+    Pull,
+    Sweep,
+    Loft,
+    /// Not playing at the ball at all.
+    Leave,
+}
+
+/// One frame of batting input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShotInput {
+    /// How fast the mouse is moving down.
+    pub swing_speed: f32,
+    /// The angle the bat is pointing, with positive angles to the leg side.
+    pub direction: f32,
+    pub block: bool,
+    pub leave: bool,
+    pub loft: bool,
+    pub sweep: bool,
+}
+
+impl ShotInput {
+    pub fn read(swing_speed: f32, direction: f32) -> Self {
+        Self {
+            swing_speed,
+            direction,
+            block: is_key_pressed(KeyCode::Space),
+            leave: is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+            loft: is_mouse_button_down(MouseButton::Left),
+            sweep: is_mouse_button_down(MouseButton::Right),
+        }
+    }
+}
+
+impl Shot {
+    /// The shot played this frame, if any. Shift leaves the ball and space blocks it. Otherwise
+    /// flicking the mouse down swings: a loft with the left button held, a sweep with the right,
+    /// and otherwise a drive, or a cut or pull when aimed far enough to the off or leg side.
+    pub fn select(input: ShotInput, tuning: &Tuning) -> Option<Self> {
+        if input.leave {
+            return Some(Self::Leave);
+        }
+        if input.block {
+            return Some(Self::Block);
+        }
+        if input.swing_speed <= tuning.batting_speed_threshold {
+            return None;
+        }
+        Some(if input.loft {
+            Self::Loft
+        } else if input.sweep {
+            Self::Sweep
+        } else if input.direction > tuning.pull_direction {
+            Self::Pull
+        } else if input.direction < -tuning.pull_direction {
+            Self::Cut
+        } else {
+            Self::Drive
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum Timing {
+    Early,
+    Good,
+    Perfect,
+    Late,
+}
+
+impl Timing {
+    /// Grades contact made `error` metres along the pitch from the batter, negative if the ball
+    /// hadn't reached them yet, for a shot with the given timing window.
+    pub fn grade(error: f32, timing_window: f32, tuning: &Tuning) -> Self {
+        let error = error / timing_window;
+        if error.abs() <= tuning.timing.perfect {
+            Self::Perfect
+        } else if error.abs() <= tuning.timing.good {
+            Self::Good
+        } else if error < 0. {
+            Self::Early
+        } else {
+            Self::Late
+        }
+    }
+
+    pub const fn edge_chance(self, tuning: &Tuning) -> f32 {
+        let chances = tuning.timing.edge_chance;
+        match self {
+            Self::Early => chances.early,
+            Self::Good => chances.good,
+            Self::Perfect => chances.perfect,
+            Self::Late => chances.late,
+        }
+    }
+}
+
+/// How a shot met the ball.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub timing: Timing,
+    pub edged: bool,
+    /// When contact was made, for showing feedback for a while afterwards.
+    pub time: f32,
+}
+
+impl Contact {
+    /// Grades contact and rolls for an edge, which is likelier the worse the timing, or `None` if
+    /// the ball is out of the bat's reach and the shot misses it.
+    pub fn new(error: f32, timing_window: f32, time: f32, tuning: &Tuning) -> Option<Self> {
+        if error.abs() > tuning.timing.reach * timing_window {
+            return None;
+        }
+        let timing = Timing::grade(error, timing_window, tuning);
+        Some(Self {
+            timing,
+            edged: random::<f32>() < timing.edge_chance(tuning),
+            time,
+        })
+    }
+}
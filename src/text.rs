@@ -0,0 +1,127 @@
+use macroquad::{
+    math::{vec2, Vec2},
+    text::{measure_text, Font, TextDimensions},
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub text: String,
+    /// Top left of the line, relative to the block's anchor.
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextBlock {
+    pub lines: Vec<Line>,
+    pub size: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextMeasureInput {
+    text: String,
+    size: u16,
+}
+
+/// Measures and lays out text in pixels with a font that is only parsed once.
+#[derive(Clone)]
+pub struct TextLayout {
+    font: Font,
+    cache: HashMap<TextMeasureInput, TextDimensions>,
+}
+
+impl TextLayout {
+    /// How many measurements to keep before starting over, since text like the score changes
+    /// every ball and would otherwise pile up forever.
+    const MAX_CACHED: usize = 1024;
+
+    pub fn new(font: Font) -> Self {
+        Self {
+            font,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn measure(&mut self, text: &str, size: u16) -> TextDimensions {
+        let input = TextMeasureInput {
+            text: text.to_string(),
+            size,
+        };
+        if let Some(dimensions) = self.cache.get(&input) {
+            return *dimensions;
+        }
+        let dimensions = measure_text(text, Some(&self.font), size, 1.);
+        if self.cache.len() >= Self::MAX_CACHED {
+            self.cache.clear();
+        }
+        self.cache.insert(input, dimensions);
+        dimensions
+    }
+
+    /// Splits `text` into lines at newlines, and between words wherever a line would otherwise be
+    /// wider than `max_width`. A single word wider than `max_width` gets a line to itself.
+    pub fn wrap(&mut self, text: &str, size: u16, max_width: Option<f32>) -> Vec<String> {
+        let Some(max_width) = max_width else {
+            return text.lines().map(str::to_string).collect();
+        };
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{line} {word}")
+                };
+                if !line.is_empty() && self.measure(&candidate, size).width > max_width {
+                    lines.push(line);
+                    line = word.to_string();
+                } else {
+                    line = candidate;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Wraps `text` and positions each centred line relative to the top centre of the block.
+    pub fn layout(
+        &mut self,
+        text: &str,
+        size: u16,
+        max_width: Option<f32>,
+        line_gap: f32,
+    ) -> TextBlock {
+        let lines = self.wrap(text, size, max_width);
+        let dimensions = lines
+            .iter()
+            .map(|line| self.measure(line, size))
+            .collect::<Vec<_>>();
+        let width = dimensions
+            .iter()
+            .map(|dimensions| dimensions.width)
+            .fold(0., f32::max);
+        let mut y = 0.;
+        let lines = lines
+            .into_iter()
+            .zip(&dimensions)
+            .enumerate()
+            .map(|(index, (text, dimensions))| {
+                if index > 0 {
+                    y += line_gap;
+                }
+                let line = Line {
+                    text,
+                    position: vec2(-dimensions.width / 2., y),
+                };
+                y += dimensions.height;
+                line
+            })
+            .collect();
+        TextBlock {
+            lines,
+            size: vec2(width, y),
+        }
+    }
+}
@@ -0,0 +1,747 @@
+use crate::{
+    layout::{Anchor, Node},
+    tuning,
+    umpire::Over,
+    Competition, Game, State, Teams,
+};
+use macroquad::math::vec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fs::{read_to_string, remove_file, write},
+    io::ErrorKind,
+    iter,
+};
+
+/// Where tournament progress is saved, relative to where the game is run from.
+const PATH: &str = "tournament.toml";
+
+/// Every team that can enter, the first being the player's.
+pub const TEAM_NAMES: [&str; 12] = [
+    "You",
+    "Rovers",
+    "Strikers",
+    "Tigers",
+    "Comets",
+    "Wanderers",
+    "Harriers",
+    "Falcons",
+    "Pilgrims",
+    "Corsairs",
+    "Stags",
+    "Nomads",
+];
+pub const PLAYER: usize = 0;
+pub const WICKETS: u32 = 10;
+const POINTS_FOR_WIN: u32 = 2;
+const POINTS_FOR_TIE: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Innings {
+    pub runs: u32,
+    pub wickets: u32,
+    /// Legal deliveries faced.
+    pub balls: u32,
+}
+
+impl Innings {
+    /// Plays out an innings between two computer sides a ball at a time, stopping early if
+    /// `target` is reached.
+    fn simulate(overs: u32, target: Option<u32>, rng: &mut impl Rng) -> Self {
+        const WICKET_CHANCE: f64 = 0.05;
+        const RUNS: [(u32, u32); 5] = [(0, 35), (1, 35), (2, 10), (4, 13), (6, 7)];
+        let mut innings = Self::default();
+        while innings.wickets < WICKETS
+            && innings.balls < overs * Over::BALLS
+            && target.is_none_or(|target| innings.runs < target)
+        {
+            innings.balls += 1;
+            if rng.gen_bool(WICKET_CHANCE) {
+                innings.wickets += 1;
+                continue;
+            }
+            let total = RUNS.iter().map(|&(_, weight)| weight).sum::<u32>();
+            let mut roll = rng.gen_range(0..total);
+            for (runs, weight) in RUNS {
+                if roll < weight {
+                    innings.runs += runs;
+                    break;
+                }
+                roll -= weight;
+            }
+        }
+        innings
+    }
+
+    /// Overs faced as used for net run rate, where being bowled out counts as facing them all.
+    fn overs(self, overs: u32) -> f32 {
+        let balls = if self.wickets >= WICKETS {
+            overs * Over::BALLS
+        } else {
+            self.balls
+        };
+        balls as f32 / Over::BALLS as f32
+    }
+}
+
+/// How a match went, from the side of the team that batted first.
+pub fn summary(first: (&str, Innings), second: (&str, Innings)) -> String {
+    match first.1.runs.cmp(&second.1.runs) {
+        Ordering::Greater => {
+            let margin = first.1.runs - second.1.runs;
+            format!("{} won by {margin} run{}", first.0, plural(margin))
+        }
+        Ordering::Less => {
+            let margin = WICKETS - second.1.wickets;
+            format!("{} won by {margin} wicket{}", second.0, plural(margin))
+        }
+        Ordering::Equal => "Match tied".to_string(),
+    }
+}
+
+const fn plural(count: u32) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stage {
+    Group(usize),
+    /// Rounds count up to the final.
+    Knockout(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub teams: [usize; 2],
+    pub stage: Stage,
+    /// Each team's innings, in the same order as `teams`.
+    pub result: Option<[Innings; 2]>,
+}
+
+impl Fixture {
+    pub const fn involves_player(&self) -> bool {
+        self.teams[0] == PLAYER || self.teams[1] == PLAYER
+    }
+
+    /// The team that won, with ties going to whoever lost fewer wickets and then to the first
+    /// team, since a knockout needs a winner.
+    pub fn winner(&self) -> Option<usize> {
+        let [first, second] = self.result?;
+        let first_won = (first.runs, second.wickets) >= (second.runs, first.wickets);
+        Some(self.teams[usize::from(!first_won)])
+    }
+}
+
+/// A team's row in a group's points table.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Standing {
+    pub team: usize,
+    pub played: u32,
+    pub won: u32,
+    pub lost: u32,
+    pub tied: u32,
+    pub points: u32,
+    runs_for: u32,
+    overs_for: f32,
+    runs_against: u32,
+    overs_against: f32,
+}
+
+impl Standing {
+    /// Runs scored per over less runs conceded per over.
+    pub fn net_run_rate(&self) -> f32 {
+        let rate = |runs, overs| {
+            if overs > 0. {
+                runs as f32 / overs
+            } else {
+                0.
+            }
+        };
+        rate(self.runs_for, self.overs_for) - rate(self.runs_against, self.overs_against)
+    }
+}
+
+/// Round-robin groups whose top teams go through to a knockout bracket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tournament {
+    pub overs: u32,
+    pub groups: Vec<Vec<usize>>,
+    /// How many teams from each group reach the knockouts.
+    pub qualifiers: usize,
+    pub fixtures: Vec<Fixture>,
+}
+
+impl Tournament {
+    /// Splits the first `teams` of [`TEAM_NAMES`] into one group, or two from eight teams up, and
+    /// schedules every group match. Four teams go through to semi-finals, or two to a final when
+    /// there are fewer than four.
+    pub fn new(teams: usize, overs: u32) -> Self {
+        assert!(
+            (2..=TEAM_NAMES.len()).contains(&teams),
+            "a tournament needs 2 to {} teams",
+            TEAM_NAMES.len()
+        );
+        let group_count = if teams >= 8 { 2 } else { 1 };
+        let qualifiers = match (group_count, teams) {
+            (2, _) => 2,
+            (_, 4..) => 4,
+            _ => 2,
+        };
+        let groups = (0..group_count)
+            .map(|group| (group..teams).step_by(group_count).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let rounds = groups
+            .iter()
+            .map(|group| round_robin(group))
+            .collect::<Vec<_>>();
+        let round_count = rounds.iter().map(Vec::len).max().unwrap_or_default();
+        // Groups take turns so they progress together.
+        let fixtures = (0..round_count)
+            .flat_map(|round| {
+                rounds.iter().enumerate().flat_map(move |(group, rounds)| {
+                    rounds
+                        .get(round)
+                        .into_iter()
+                        .flatten()
+                        .map(move |&teams| Fixture {
+                            teams,
+                            stage: Stage::Group(group),
+                            result: None,
+                        })
+                })
+            })
+            .collect();
+        Self {
+            overs,
+            groups,
+            qualifiers,
+            fixtures,
+        }
+    }
+
+    /// The points table for a group, best first, with ties broken by net run rate.
+    pub fn standings(&self, group: usize) -> Vec<Standing> {
+        let mut standings = self.groups[group]
+            .iter()
+            .map(|&team| Standing {
+                team,
+                ..Standing::default()
+            })
+            .collect::<Vec<_>>();
+        for fixture in &self.fixtures {
+            let (Stage::Group(fixture_group), Some(result)) = (fixture.stage, fixture.result)
+            else {
+                continue;
+            };
+            if fixture_group != group {
+                continue;
+            }
+            for side in 0..2 {
+                let (us, them) = (result[side], result[1 - side]);
+                let Some(standing) = standings
+                    .iter_mut()
+                    .find(|standing| standing.team == fixture.teams[side])
+                else {
+                    continue;
+                };
+                standing.played += 1;
+                match us.runs.cmp(&them.runs) {
+                    Ordering::Greater => {
+                        standing.won += 1;
+                        standing.points += POINTS_FOR_WIN;
+                    }
+                    Ordering::Less => standing.lost += 1,
+                    Ordering::Equal => {
+                        standing.tied += 1;
+                        standing.points += POINTS_FOR_TIE;
+                    }
+                }
+                standing.runs_for += us.runs;
+                standing.overs_for += us.overs(self.overs);
+                standing.runs_against += them.runs;
+                standing.overs_against += them.overs(self.overs);
+            }
+        }
+        standings.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then(b.net_run_rate().total_cmp(&a.net_run_rate()))
+        });
+        standings
+    }
+
+    pub fn next_fixture(&self) -> Option<usize> {
+        self.fixtures
+            .iter()
+            .position(|fixture| fixture.result.is_none())
+    }
+
+    /// Plays out every fixture before the player's next one, returning that one.
+    pub fn simulate_until_player(&mut self, rng: &mut impl Rng) -> Option<usize> {
+        while let Some(index) = self.next_fixture() {
+            if self.fixtures[index].involves_player() {
+                return Some(index);
+            }
+            let first = Innings::simulate(self.overs, None, rng);
+            let second = Innings::simulate(self.overs, Some(first.runs + 1), rng);
+            self.record(index, [first, second]);
+        }
+        None
+    }
+
+    /// Records a fixture's result, drawing up the next knockout round once every match before it
+    /// has been played.
+    pub fn record(&mut self, fixture: usize, result: [Innings; 2]) {
+        self.fixtures[fixture].result = Some(result);
+        if self.next_fixture().is_some() || self.champion().is_some() {
+            return;
+        }
+        let last_round = self
+            .fixtures
+            .iter()
+            .rev()
+            .find_map(|fixture| match fixture.stage {
+                Stage::Knockout(round) => Some(round),
+                Stage::Group(_) => None,
+            });
+        let (round, teams) = match last_round {
+            Some(round) => (
+                round + 1,
+                self.fixtures
+                    .iter()
+                    .filter(|fixture| fixture.stage == Stage::Knockout(round))
+                    .filter_map(Fixture::winner)
+                    .collect::<Vec<_>>(),
+            ),
+            None => (0, self.seeded_qualifiers()),
+        };
+        // Best against worst, so the top seeds can only meet in the final.
+        let pairs = teams.len() / 2;
+        self.fixtures.extend((0..pairs).map(|index| Fixture {
+            teams: [teams[index], teams[teams.len() - 1 - index]],
+            stage: Stage::Knockout(round),
+            result: None,
+        }));
+    }
+
+    /// Group winners first, then runners-up and so on, so that pairing the best with the worst
+    /// keeps teams from the same group apart.
+    fn seeded_qualifiers(&self) -> Vec<usize> {
+        let standings = (0..self.groups.len())
+            .map(|group| self.standings(group))
+            .collect::<Vec<_>>();
+        (0..self.qualifiers)
+            .flat_map(|rank| standings.iter().map(move |standings| standings[rank].team))
+            .collect()
+    }
+
+    /// The winner of the final, once it's been played.
+    pub fn champion(&self) -> Option<usize> {
+        let last = self.fixtures.last()?;
+        let final_round = matches!(last.stage, Stage::Knockout(_))
+            && self
+                .fixtures
+                .iter()
+                .filter(|fixture| fixture.stage == last.stage)
+                .count()
+                == 1;
+        final_round.then(|| last.winner()).flatten()
+    }
+
+    /// What the round a fixture is in is called.
+    pub fn stage_name(&self, stage: Stage) -> String {
+        match stage {
+            Stage::Group(_) if self.groups.len() == 1 => "League".to_string(),
+            Stage::Group(group) => format!("Group {}", char::from(b'A' + group as u8)),
+            Stage::Knockout(round) => {
+                let teams = (self.groups.len() * self.qualifiers) >> round;
+                match teams {
+                    2 => "Final".to_string(),
+                    4 => "Semi-final".to_string(),
+                    teams => format!("Round of {teams}"),
+                }
+            }
+        }
+    }
+
+    /// The saved tournament, if there is one.
+    pub fn load() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        match read_to_string(PATH) {
+            Ok(text) => Ok(Some(toml::from_str(&text)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(write(PATH, toml::to_string(self)?)?)
+    }
+
+    /// Forgets the saved tournament, if there is one.
+    pub fn delete() -> Result<(), Box<dyn std::error::Error>> {
+        match remove_file(PATH) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Every pairing in a group, split into rounds where each team plays at most once, using the
+/// circle method.
+fn round_robin(teams: &[usize]) -> Vec<Vec<[usize; 2]>> {
+    let mut slots = teams.iter().copied().map(Some).collect::<Vec<_>>();
+    if slots.len() % 2 == 1 {
+        // Whoever is drawn against the bye sits the round out.
+        slots.push(None);
+    }
+    let count = slots.len();
+    (0..count - 1)
+        .map(|_| {
+            let round = (0..count / 2)
+                .filter_map(|index| Some([slots[index]?, slots[count - 1 - index]?]))
+                .collect();
+            // The first team stays put and everyone else moves round one place.
+            slots[1..].rotate_right(1);
+            round
+        })
+        .collect()
+}
+
+impl Game<'_> {
+    /// How many teams a new tournament can have, cycled through by its button.
+    pub const TOURNAMENT_TEAMS: [usize; 4] = [8, 4, 6, 12];
+
+    pub fn draw_tournament(&mut self) {
+        const HEADING_TOP: f32 = 4.;
+        const GAP: f32 = 3.;
+        const TABLE_GAP: f32 = 8.;
+        const TABLE_TEXT_SIZE: u16 = 3;
+        let State::Tournament { teams } = self.state else {
+            unreachable!()
+        };
+        let Some(tournament) = &self.tournament else {
+            self.draw_tournament_setup(teams);
+            return;
+        };
+
+        let tables = (0..tournament.groups.len()).map(|group| {
+            let standings = tournament.standings(group);
+            let column = |heading: &str, cell: &dyn Fn(&Standing) -> String| {
+                Node::column(
+                    Self::LINE_GAP,
+                    iter::once(Node::text(heading, TABLE_TEXT_SIZE)).chain(
+                        standings
+                            .iter()
+                            .map(|standing| Node::text(cell(standing), TABLE_TEXT_SIZE)),
+                    ),
+                )
+            };
+            Node::column(
+                Self::LINE_GAP,
+                [
+                    Node::text(tournament.stage_name(Stage::Group(group)), Self::TEXT_SIZE),
+                    Node::row(
+                        GAP,
+                        [
+                            column("Team", &|standing| TEAM_NAMES[standing.team].to_string()),
+                            column("P", &|standing| standing.played.to_string()),
+                            column("W", &|standing| standing.won.to_string()),
+                            column("L", &|standing| standing.lost.to_string()),
+                            column("T", &|standing| standing.tied.to_string()),
+                            column("Pts", &|standing| standing.points.to_string()),
+                            column("NRR", &|standing| {
+                                format!("{:+.2}", standing.net_run_rate())
+                            }),
+                        ],
+                    ),
+                ],
+            )
+        });
+        let next = tournament
+            .fixtures
+            .iter()
+            .find(|fixture| fixture.involves_player() && fixture.result.is_none());
+        let status = match (tournament.champion(), next) {
+            (Some(champion), _) => format!("{} won the tournament!", TEAM_NAMES[champion]),
+            (None, Some(fixture)) => format!(
+                "{}: {} v {}",
+                tournament.stage_name(fixture.stage),
+                TEAM_NAMES[fixture.teams[0]],
+                TEAM_NAMES[fixture.teams[1]]
+            ),
+            (None, None) => "Other matches to play".to_string(),
+        };
+        let mut buttons = Vec::new();
+        if tournament.champion().is_none() {
+            let label = if next.is_some() {
+                "Play next match"
+            } else {
+                "Continue"
+            };
+            buttons.push(
+                Node::text(label, Self::TEXT_SIZE)
+                    .padding(Self::BUTTON_PADDING)
+                    .button(0),
+            );
+        }
+        buttons.push(
+            Node::text("New tournament", Self::TEXT_SIZE)
+                .padding(Self::BUTTON_PADDING)
+                .button(1),
+        );
+        let root = Node::column(
+            GAP,
+            [
+                Node::text("Tournament", Self::HEADING_TEXT_SIZE),
+                Node::row(TABLE_GAP, tables),
+                Node::text(status, Self::TEXT_SIZE),
+                Node::row(GAP, buttons),
+            ],
+        );
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Top, vec2(0., HEADING_TOP));
+        });
+        match activated {
+            Some(0) => {
+                let tournament = self.tournament.take().unwrap();
+                self.start_tournament(tournament);
+            }
+            Some(_) => {
+                if let Err(error) = Tournament::delete() {
+                    eprintln!("couldn't delete the saved tournament: {error}");
+                }
+                self.tournament = None;
+                self.state = State::Tournament { teams };
+            }
+            None => {}
+        }
+    }
+
+    /// Picks how many teams to start a tournament with.
+    fn draw_tournament_setup(&mut self, teams: usize) {
+        const HEADING_TOP: f32 = 4.;
+        const GAP: f32 = 3.;
+        let root = Node::column(
+            GAP,
+            [
+                Node::text("Tournament", Self::HEADING_TEXT_SIZE),
+                Node::text(format!("Teams: {teams}"), Self::TEXT_SIZE)
+                    .padding(Self::BUTTON_PADDING)
+                    .button(0),
+                Node::text("Start", Self::TEXT_SIZE)
+                    .padding(Self::BUTTON_PADDING)
+                    .button(1),
+            ],
+        );
+        let mut activated = None;
+        Self::window(|ui| {
+            activated = self.draw_layout(ui, &root, Anchor::Top, vec2(0., HEADING_TOP));
+        });
+        match activated {
+            Some(0) => {
+                let index = Self::TOURNAMENT_TEAMS
+                    .iter()
+                    .position(|&count| count == teams)
+                    .unwrap_or_default();
+                self.state = State::Tournament {
+                    teams: Self::TOURNAMENT_TEAMS[(index + 1) % Self::TOURNAMENT_TEAMS.len()],
+                };
+            }
+            Some(_) => self.start_tournament(Tournament::new(teams, tuning::current().overs)),
+            None => {}
+        }
+    }
+
+    /// Plays out the computer sides' matches up to the player's next fixture and saves, then goes
+    /// to the toss for it, or back to the tables if the player has no more matches.
+    fn start_tournament(&mut self, mut tournament: Tournament) {
+        self.competition = tournament
+            .simulate_until_player(&mut rand::thread_rng())
+            .map(Competition::Tournament);
+        if let Err(error) = tournament.save() {
+            eprintln!("couldn't save the tournament: {error}");
+        }
+        let teams = tournament.groups.iter().map(Vec::len).sum();
+        self.tournament = Some(tournament);
+        self.state = if self.competition.is_some() {
+            State::PickingSide
+        } else {
+            State::Tournament { teams }
+        };
+    }
+
+    pub fn record_fixture(tournament: &mut Tournament, fixture: usize, teams: &Teams) {
+        let result = tournament.fixtures[fixture].teams.map(|team| {
+            if TEAM_NAMES[team] == teams.batting.name {
+                teams.batting.innings()
+            } else {
+                teams.fielding.innings()
+            }
+        });
+        tournament.record(fixture, result);
+        if let Err(error) = tournament.save() {
+            eprintln!("couldn't save the tournament: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A result where the team earlier in [`TEAM_NAMES`] wins, so standings follow team order.
+    fn result(teams: [usize; 2]) -> [Innings; 2] {
+        let innings = |team| Innings {
+            runs: 200 - team as u32,
+            wickets: 5,
+            balls: 60,
+        };
+        teams.map(innings)
+    }
+
+    /// Plays every fixture in order, returning the knockout rounds as they were drawn up.
+    fn play_out(tournament: &mut Tournament) -> Vec<Vec<[usize; 2]>> {
+        let mut rounds = Vec::<Vec<[usize; 2]>>::new();
+        while let Some(index) = tournament.next_fixture() {
+            let fixture = tournament.fixtures[index];
+            if let Stage::Knockout(round) = fixture.stage {
+                if rounds.len() <= round {
+                    rounds.push(Vec::new());
+                }
+                rounds[round].push(fixture.teams);
+            }
+            assert_eq!(tournament.champion(), None);
+            tournament.record(index, result(fixture.teams));
+        }
+        rounds
+    }
+
+    fn assert_plays_everyone_once(rounds: &[Vec<[usize; 2]>], teams: &[usize]) {
+        let mut pairs = rounds
+            .iter()
+            .flatten()
+            .map(|&[first, second]| [first.min(second), first.max(second)])
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let mut expected = teams
+            .iter()
+            .enumerate()
+            .flat_map(|(index, &first)| {
+                teams[index + 1..]
+                    .iter()
+                    .map(move |&second| [first, second])
+            })
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        assert_eq!(pairs, expected);
+        for round in rounds {
+            let mut playing = round.iter().flatten().collect::<Vec<_>>();
+            playing.sort_unstable();
+            playing.dedup();
+            assert_eq!(
+                playing.len(),
+                round.len() * 2,
+                "a team plays twice in {round:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_robin_gives_odd_groups_a_bye() {
+        let rounds = round_robin(&[0, 1, 2]);
+        assert_eq!(rounds.len(), 3);
+        assert!(rounds.iter().all(|round| round.len() == 1));
+        assert_plays_everyone_once(&rounds, &[0, 1, 2]);
+
+        let teams = [0, 1, 2, 3, 4, 5, 6];
+        let rounds = round_robin(&teams);
+        assert_eq!(rounds.len(), 7);
+        assert!(rounds.iter().all(|round| round.len() == 3));
+        assert_plays_everyone_once(&rounds, &teams);
+    }
+
+    #[test]
+    fn round_robin_pairs_even_groups_every_round() {
+        let teams = [0, 2, 4, 6];
+        let rounds = round_robin(&teams);
+        assert_eq!(rounds.len(), 3);
+        assert!(rounds.iter().all(|round| round.len() == 2));
+        assert_plays_everyone_once(&rounds, &teams);
+    }
+
+    #[test]
+    fn winner_goes_by_runs_then_wickets_then_batting_first() {
+        let fixture = |first: Innings, second: Innings| Fixture {
+            teams: [3, 5],
+            stage: Stage::Knockout(0),
+            result: Some([first, second]),
+        };
+        let innings = |runs, wickets| Innings {
+            runs,
+            wickets,
+            balls: 60,
+        };
+        assert_eq!(fixture(innings(150, 9), innings(149, 2)).winner(), Some(3));
+        assert_eq!(fixture(innings(150, 2), innings(151, 9)).winner(), Some(5));
+        assert_eq!(fixture(innings(150, 2), innings(150, 9)).winner(), Some(3));
+        assert_eq!(fixture(innings(150, 9), innings(150, 2)).winner(), Some(5));
+        assert_eq!(fixture(innings(150, 4), innings(150, 4)).winner(), Some(3));
+        let unplayed = Fixture {
+            result: None,
+            ..fixture(innings(0, 0), innings(0, 0))
+        };
+        assert_eq!(unplayed.winner(), None);
+    }
+
+    #[test]
+    fn three_teams_play_a_final() {
+        let mut tournament = Tournament::new(3, 10);
+        assert_eq!(tournament.groups, [vec![0, 1, 2]]);
+        assert_eq!(tournament.fixtures.len(), 3);
+        let knockouts = play_out(&mut tournament);
+        assert_eq!(knockouts, [vec![[0, 1]]]);
+        assert_eq!(tournament.stage_name(Stage::Knockout(0)), "Final");
+        assert_eq!(tournament.champion(), Some(0));
+    }
+
+    #[test]
+    fn seven_teams_play_semi_finals_from_one_group() {
+        let mut tournament = Tournament::new(7, 10);
+        assert_eq!(tournament.groups, [(0..7).collect::<Vec<_>>()]);
+        assert_eq!(tournament.fixtures.len(), 21);
+        let knockouts = play_out(&mut tournament);
+        assert_eq!(
+            tournament
+                .standings(0)
+                .iter()
+                .map(|standing| standing.team)
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5, 6]
+        );
+        // First plays fourth and second plays third.
+        assert_eq!(knockouts, [vec![[0, 3], [1, 2]], vec![[0, 1]]]);
+        assert_eq!(tournament.stage_name(Stage::Knockout(0)), "Semi-final");
+        assert_eq!(tournament.champion(), Some(0));
+    }
+
+    #[test]
+    fn eight_teams_split_into_two_groups() {
+        let mut tournament = Tournament::new(8, 10);
+        assert_eq!(tournament.groups, [vec![0, 2, 4, 6], vec![1, 3, 5, 7]]);
+        assert_eq!(tournament.qualifiers, 2);
+        assert_eq!(tournament.fixtures.len(), 12);
+        let knockouts = play_out(&mut tournament);
+        assert_eq!(tournament.seeded_qualifiers(), [0, 1, 2, 3]);
+        // A1 v B2 and B1 v A2.
+        assert_eq!(knockouts, [vec![[0, 3], [1, 2]], vec![[0, 1]]]);
+        assert_eq!(tournament.stage_name(Stage::Group(1)), "Group B");
+        assert_eq!(tournament.champion(), Some(0));
+    }
+}
@@ -0,0 +1,37 @@
+use macroquad::math::Vec3;
+
+/// Where a predicted path first bounces, as its lowest point before it starts rising again, with
+/// that point's index.
+pub fn bounce(path: &[Vec3]) -> Option<(usize, Vec3)> {
+    path.windows(3)
+        .position(|points| points[1].y <= points[0].y && points[1].y < points[2].y)
+        .map(|index| (index + 1, path[index + 1]))
+}
+
+/// Where a predicted path first crosses the plane at `z`, interpolated between the points either
+/// side, with the index of the point after it.
+pub fn crossing(path: &[Vec3], z: f32) -> Option<(usize, Vec3)> {
+    path.windows(2).enumerate().find_map(|(index, pair)| {
+        let (start, end) = (pair[0], pair[1]);
+        let crosses = (start.z < z && z <= end.z) || (end.z < z && z <= start.z);
+        if !crosses {
+            return None;
+        }
+        Some((
+            index + 1,
+            start.lerp(end, (z - start.z) / (end.z - start.z)),
+        ))
+    })
+}
+
+/// Where a delivery's predicted path bounces before crossing the batter's crease at `z`, and where
+/// it crosses it. Neither is known if it never reaches the crease.
+pub fn delivery(path: &[Vec3], z: f32) -> (Option<Vec3>, Option<Vec3>) {
+    let Some((arrival_index, arrival)) = crossing(path, z) else {
+        return (None, None);
+    };
+    let pitch = bounce(path)
+        .filter(|&(index, _)| index < arrival_index)
+        .map(|(_, pitch)| pitch);
+    (pitch, Some(arrival))
+}
@@ -0,0 +1,236 @@
+use crate::{curve::Polynomial, shot::Shot};
+use macroquad::math::{Vec2, Vec3};
+use serde::{de::Error, Deserialize, Deserializer};
+use std::{
+    fs::{metadata, read_to_string},
+    path::Path,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use toml::{Table, Value};
+
+/// Where a tuning file overriding the built-in one is looked for, relative to where the game is run
+/// from.
+const PATH: &str = "tuning.toml";
+/// How often to look at whether the tuning file has changed.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Gameplay numbers that designers can tweak without recompiling. See `tuning.toml` for what each
+/// one does.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tuning {
+    pub overs: u32,
+    pub ball_delay: f32,
+    pub run_up_time: f32,
+    pub run_up_length: f32,
+    #[serde(deserialize_with = "vec3")]
+    pub release_offset: Vec3,
+    pub ball_force: f32,
+    pub ball_mass: f32,
+    #[serde(deserialize_with = "vec2")]
+    pub delta_multiplier: Vec2,
+    pub batting_speed_threshold: f32,
+    pub batting_direction_limit: f32,
+    pub pull_direction: f32,
+    pub fielder_speed: f32,
+    pub fielder_reach: f32,
+    pub catch_height: f32,
+    pub throw_speed: f32,
+    pub running_speed: f32,
+    #[serde(deserialize_with = "vec3")]
+    pub camera_position: Vec3,
+    #[serde(deserialize_with = "vec3")]
+    pub camera_target: Vec3,
+    pub shots: Shots,
+    pub timing: TimingTuning,
+    pub umpire: UmpireTuning,
+    pub nets: NetsTuning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShotTuning {
+    pub elevation: f32,
+    pub power: f32,
+    pub timing_window: f32,
+}
+
+/// How each shot that plays at the ball hits it.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Shots {
+    pub block: ShotTuning,
+    pub drive: ShotTuning,
+    pub cut: ShotTuning,
+    pub pull: ShotTuning,
+    pub sweep: ShotTuning,
+    pub loft: ShotTuning,
+}
+
+impl Shots {
+    /// The tuning for `shot`, or `None` for a leave, which doesn't hit the ball.
+    pub const fn get(&self, shot: Shot) -> Option<ShotTuning> {
+        Some(match shot {
+            Shot::Block => self.block,
+            Shot::Drive => self.drive,
+            Shot::Cut => self.cut,
+            Shot::Pull => self.pull,
+            Shot::Sweep => self.sweep,
+            Shot::Loft => self.loft,
+            Shot::Leave => return None,
+        })
+    }
+}
+
+fn vec2<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec2, D::Error> {
+    <[f32; 2]>::deserialize(deserializer).map(Vec2::from)
+}
+
+fn vec3<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec3, D::Error> {
+    <[f32; 3]>::deserialize(deserializer).map(Vec3::from)
+}
+
+const DEFAULT: &str = include_str!("tuning.toml");
+
+impl Tuning {
+    /// Parses a tuning file, taking anything it leaves out from the built-in one.
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        let mut table = DEFAULT.parse::<Table>()?;
+        merge(&mut table, source.parse()?);
+        table.try_into()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::parse(&read_to_string(path)?)?)
+    }
+}
+
+/// Overlays `overrides` onto `table`, merging nested tables so a file can change one field of one.
+fn merge(table: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(table)), Value::Table(overrides)) => merge(table, overrides),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimingTuning {
+    pub perfect: f32,
+    pub good: f32,
+    pub reach: f32,
+    pub edge_chance: EdgeChance,
+    pub edge_speed: f32,
+    pub edge_angle: f32,
+    /// How much of the swing's velocity goes into the ball, by how far from the bat's sweet spot
+    /// it's met as a fraction of the shot's timing window.
+    #[serde(rename = "velocity_samples", deserialize_with = "velocity_multiplier")]
+    pub velocity_multiplier: Polynomial<4>,
+}
+
+/// Fits a cubic to `[error, multiplier]` samples, rejecting one that doesn't keep falling as the
+/// timing gets worse across the timing window.
+fn velocity_multiplier<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Polynomial<4>, D::Error> {
+    let samples = Vec::<(f32, f32)>::deserialize(deserializer)?;
+    let curve = Polynomial::fit(&samples)
+        .ok_or_else(|| D::Error::custom("need samples at 4 or more different errors"))?;
+    if !curve.derivative().roots(0.0..=1.).is_empty() {
+        return Err(D::Error::custom(
+            "the samples have to fit a curve that keeps falling between errors of 0 and 1",
+        ));
+    }
+    Ok(curve)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EdgeChance {
+    pub early: f32,
+    pub good: f32,
+    pub perfect: f32,
+    pub late: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UmpireTuning {
+    pub stride_variation: f32,
+    pub waist_height: f32,
+    pub shoulder_height: f32,
+    pub bouncers_per_over: u32,
+    pub wide_corridor: f32,
+    pub extra_runs: u32,
+    pub error_chance: f32,
+    pub edge_error_chance: f32,
+    pub close_run_out: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetsTuning {
+    pub slow_speed: f32,
+    pub medium_speed: f32,
+    pub fast_speed: f32,
+    pub swing_force: f32,
+    pub yorker_length: f32,
+    pub full_length: f32,
+    pub good_length: f32,
+    pub short_length: f32,
+    pub full_toss_height: f32,
+    pub leg_line: f32,
+    pub off_line: f32,
+    pub outside_off_line: f32,
+}
+
+struct Loaded {
+    tuning: Tuning,
+    /// When the tuning file was last modified as of loading it, or `None` for the built-in one.
+    modified: Option<SystemTime>,
+    /// When the tuning file was last looked at.
+    checked: Option<Instant>,
+}
+
+static CURRENT: LazyLock<Mutex<Loaded>> = LazyLock::new(|| {
+    Mutex::new(Loaded {
+        tuning: Tuning::parse("").unwrap(),
+        modified: None,
+        checked: None,
+    })
+});
+
+pub fn current() -> Tuning {
+    CURRENT.lock().unwrap().tuning
+}
+
+/// Loads the tuning file if it has been modified since it was last loaded, only looking at it
+/// every [`CHECK_INTERVAL`] so it can be called every frame. If it can't be parsed, the previous
+/// tuning is kept until it is fixed.
+pub fn reload_if_changed() {
+    let mut current = CURRENT.lock().unwrap();
+    let now = Instant::now();
+    if current
+        .checked
+        .is_some_and(|checked| now - checked < CHECK_INTERVAL)
+    {
+        return;
+    }
+    current.checked = Some(now);
+    let Ok(modified) = metadata(PATH).and_then(|metadata| metadata.modified()) else {
+        return;
+    };
+    if current.modified == Some(modified) {
+        return;
+    }
+    current.modified = Some(modified);
+    match Tuning::load(Path::new(PATH)) {
+        Ok(tuning) => current.tuning = tuning,
+        Err(error) => eprintln!("keeping previous tuning, {PATH} is invalid: {error}"),
+    }
+}
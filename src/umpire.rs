@@ -0,0 +1,205 @@
+use crate::tuning::Tuning;
+use macroquad::math::Vec3;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoBall {
+    /// The bowler's front foot landed entirely past the popping crease.
+    FrontFoot,
+    /// The ball reached the batter above their waist without bouncing.
+    FullToss,
+    /// More bouncers than are allowed in an over.
+    Bouncer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    NoBall(NoBall),
+    Wide,
+}
+
+impl Display for Call {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoBall(NoBall::FrontFoot) => write!(f, "No ball - front foot"),
+            Self::NoBall(NoBall::FullToss) => write!(f, "No ball - full toss"),
+            Self::NoBall(NoBall::Bouncer) => write!(f, "No ball - bouncer"),
+            Self::Wide => write!(f, "Wide"),
+        }
+    }
+}
+
+/// What the umpire sees of a delivery as it's released.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delivery {
+    /// How far the bowler's front foot landed past the popping crease, negative if behind it.
+    pub overstep: f32,
+    /// Where the ball is predicted to bounce before reaching the batter, if it does.
+    pub pitch: Option<Vec3>,
+    /// Where the ball is predicted to cross the batter's popping crease, if it does.
+    pub arrival: Option<Vec3>,
+}
+
+/// The state of the current over that carries from one delivery to the next.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Over {
+    pub legal_balls: u32,
+    pub bouncers: u32,
+    /// Whether the next delivery is a free hit, after a no-ball.
+    pub free_hit: bool,
+}
+
+impl Over {
+    pub const BALLS: u32 = 6;
+
+    /// Judges a delivery as it's released, counting it towards the over's bouncers. A no-ball
+    /// takes precedence over a wide. Wides are only provisional, as hitting the ball cancels them.
+    pub fn judge(&mut self, delivery: Delivery, tuning: &Tuning) -> Option<Call> {
+        let umpire = tuning.umpire;
+        if delivery.overstep > 0. {
+            return Some(Call::NoBall(NoBall::FrontFoot));
+        }
+        let arrival = delivery.arrival?;
+        if delivery.pitch.is_none() && arrival.y > umpire.waist_height {
+            return Some(Call::NoBall(NoBall::FullToss));
+        }
+        if delivery.pitch.is_some() && arrival.y > umpire.shoulder_height {
+            self.bouncers += 1;
+            if self.bouncers > umpire.bouncers_per_over {
+                return Some(Call::NoBall(NoBall::Bouncer));
+            }
+        }
+        (arrival.x.abs() > umpire.wide_corridor).then_some(Call::Wide)
+    }
+
+    /// Records the umpire's final call, returning the extra runs it awards. Extras don't count
+    /// towards the over, and a no-ball makes the next delivery a free hit. The free hit carries
+    /// over until a legal delivery is bowled.
+    pub fn record(&mut self, call: Option<Call>, tuning: &Tuning) -> u32 {
+        self.free_hit = matches!(call, Some(Call::NoBall(_))) || (self.free_hit && call.is_some());
+        if call.is_some() {
+            return tuning.umpire.extra_runs;
+        }
+        self.legal_balls += 1;
+        if self.legal_balls == Self::BALLS {
+            *self = Self::default();
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use macroquad::math::vec3;
+
+    /// A delivery landing behind the popping crease, pitching short of the batter when `pitched`,
+    /// and reaching them at `x` across the pitch and `y` high.
+    fn delivery(overstep: f32, pitched: bool, x: f32, y: f32) -> Delivery {
+        Delivery {
+            overstep,
+            pitch: pitched.then(|| vec3(0., 0., 5.)),
+            arrival: Some(vec3(x, y, 10.)),
+        }
+    }
+
+    #[test]
+    fn judges_each_delivery() {
+        let tuning = Tuning::parse("").unwrap();
+        let umpire = tuning.umpire;
+        let cases = [
+            ("legal", delivery(-0.5, true, 0., 0.5), None),
+            (
+                "front foot",
+                delivery(0.1, true, 0., 0.5),
+                Some(Call::NoBall(NoBall::FrontFoot)),
+            ),
+            (
+                "front foot over a wide",
+                delivery(0.1, true, umpire.wide_corridor + 1., 0.5),
+                Some(Call::NoBall(NoBall::FrontFoot)),
+            ),
+            (
+                "full toss below the waist",
+                delivery(-0.5, false, 0., 0.8),
+                None,
+            ),
+            (
+                "full toss above the waist",
+                delivery(-0.5, false, 0., umpire.waist_height + 0.1),
+                Some(Call::NoBall(NoBall::FullToss)),
+            ),
+            (
+                "inside the wide corridor",
+                delivery(-0.5, true, umpire.wide_corridor - 0.1, 0.5),
+                None,
+            ),
+            (
+                "outside the wide corridor",
+                delivery(-0.5, true, -umpire.wide_corridor - 0.1, 0.5),
+                Some(Call::Wide),
+            ),
+            (
+                "never reaching the batter",
+                Delivery {
+                    arrival: None,
+                    ..delivery(-0.5, true, 0., 0.5)
+                },
+                None,
+            ),
+        ];
+        for (name, delivery, call) in cases {
+            assert_eq!(Over::default().judge(delivery, &tuning), call, "{name}");
+        }
+    }
+
+    #[test]
+    fn only_bouncers_past_the_allowance_are_no_balls() {
+        let tuning = Tuning::parse("").unwrap();
+        assert_eq!(tuning.umpire.bouncers_per_over, 1);
+        let bouncer = delivery(-0.5, true, 0., tuning.umpire.shoulder_height + 0.1);
+        let mut over = Over::default();
+        let calls = [None, Some(Call::NoBall(NoBall::Bouncer))];
+        for (index, call) in calls.into_iter().enumerate() {
+            assert_eq!(over.judge(bouncer, &tuning), call, "bouncer {}", index + 1);
+        }
+        assert_eq!(over.bouncers, 2);
+    }
+
+    #[test]
+    fn records_extras_and_free_hits() {
+        let tuning = Tuning::parse("").unwrap();
+        let extra = tuning.umpire.extra_runs;
+        let no_ball = Some(Call::NoBall(NoBall::FrontFoot));
+        // Each call, with the runs it awards, the legal balls and whether the next is a free hit.
+        let cases = [
+            (None, 0, 1, false),
+            (no_ball, extra, 1, true),
+            // A wide after a no-ball keeps the free hit going.
+            (Some(Call::Wide), extra, 1, true),
+            (None, 0, 2, false),
+            (Some(Call::Wide), extra, 2, false),
+            (no_ball, extra, 2, true),
+            (no_ball, extra, 2, true),
+            (None, 0, 3, false),
+        ];
+        let mut over = Over::default();
+        for (index, (call, runs, legal_balls, free_hit)) in cases.into_iter().enumerate() {
+            assert_eq!(over.record(call, &tuning), runs, "delivery {index}");
+            assert_eq!(over.legal_balls, legal_balls, "delivery {index}");
+            assert_eq!(over.free_hit, free_hit, "delivery {index}");
+        }
+    }
+
+    #[test]
+    fn the_over_ends_after_six_legal_balls() {
+        let tuning = Tuning::parse("").unwrap();
+        let mut over = Over {
+            legal_balls: Over::BALLS - 1,
+            bouncers: 1,
+            free_hit: true,
+        };
+        over.record(None, &tuning);
+        assert_eq!(over, Over::default());
+    }
+}
@@ -0,0 +1,39 @@
+//! Checks the sprites the build script exports when no Aseprite binary is configured.
+use retro_wicket_aseprite::Aseprite;
+use std::{
+    fs::{read, read_dir, File},
+    path::Path,
+};
+
+#[test]
+fn exports_every_frame_without_aseprite() {
+    if Path::new("src/aseprite_path").exists() {
+        eprintln!("skipped, as the sprites were exported by Aseprite");
+        return;
+    }
+    for entry in read_dir("src/sprites").unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let aseprite = Aseprite::parse(&read(&path).unwrap()).unwrap();
+        for frame in 0..aseprite.frames.len() {
+            let suffix = if aseprite.frames.len() == 1 {
+                String::new()
+            } else {
+                (frame + 1).to_string()
+            };
+            let file = format!("{}/{name}{suffix}.png", env!("OUT_DIR"));
+            let mut reader = png::Decoder::new(File::open(&file).unwrap())
+                .read_info()
+                .unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut pixels).unwrap();
+            assert_eq!(
+                (info.width, info.height),
+                (aseprite.width.into(), aseprite.height.into()),
+                "{file}"
+            );
+            assert_eq!(info.color_type, png::ColorType::Rgba, "{file}");
+            assert!(pixels == aseprite.render(frame).unwrap(), "{file}");
+        }
+    }
+}